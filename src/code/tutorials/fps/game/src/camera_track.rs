@@ -0,0 +1,231 @@
+use crate::{
+    player::Player,
+    trigger::{update_trigger, with_player, TriggerHandler, TriggerVolume},
+    Game,
+};
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{graph::Graph, node::Node},
+    script::{ScriptContext, ScriptTrait},
+};
+
+// ANCHOR: camera_track_easing
+#[derive(Visit, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraTrackEasing {
+    // The camera moves between the points at a constant pace.
+    #[default]
+    Linear,
+    // The camera slows down near every point, as if it stops to show something.
+    Smooth,
+}
+// ANCHOR_END: camera_track_easing
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "4ae2e837-9dbc-4108-a144-9c18b29ae53d")]
+#[visit(optional)]
+pub struct CameraTrack {
+    // ANCHOR: camera_track_fields
+    // Nodes that the camera passes through in order, their rotations define where it looks.
+    waypoints: InheritableVariable<Vec<Handle<Node>>>,
+
+    // A camera that shows the track, it is enabled only during the playback. It must not have a
+    // parent, because it is moved in world space.
+    camera: InheritableVariable<Handle<Node>>,
+
+    // Time (in seconds) that the whole track takes.
+    duration: InheritableVariable<f32>,
+
+    easing: InheritableVariable<CameraTrackEasing>,
+
+    // Plays the track once the player enters the volume. Otherwise it is played only by `play`.
+    play_on_enter: InheritableVariable<bool>,
+
+    #[reflect(hidden)]
+    trigger: TriggerVolume,
+
+    // A rigid body of the player, whose control is disabled during the playback.
+    #[reflect(hidden)]
+    player: Handle<Node>,
+
+    // Time since the beginning of the playback, it is set only while the track plays.
+    #[reflect(hidden)]
+    time: Option<f32>,
+
+    #[reflect(hidden)]
+    played: bool,
+    // ANCHOR_END: camera_track_fields
+}
+
+impl Default for CameraTrack {
+    fn default() -> Self {
+        Self {
+            waypoints: Default::default(),
+            camera: Default::default(),
+            duration: 5.0.into(),
+            easing: Default::default(),
+            play_on_enter: true.into(),
+            trigger: Default::default(),
+            player: Default::default(),
+            time: None,
+            played: false,
+        }
+    }
+}
+
+// ANCHOR: catmull_rom
+// A point on a smooth curve that goes through `p1` and `p2`, the other two points define its
+// tangents. The parameter is in [0; 1] range.
+fn catmull_rom(
+    p0: Vector3<f32>,
+    p1: Vector3<f32>,
+    p2: Vector3<f32>,
+    p3: Vector3<f32>,
+    t: f32,
+) -> Vector3<f32> {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1.scale(2.0)
+        + (p2 - p0).scale(t)
+        + (p0.scale(2.0) - p1.scale(5.0) + p2.scale(4.0) - p3).scale(t2)
+        + (p1.scale(3.0) - p0 - p2.scale(3.0) + p3).scale(t3))
+    .scale(0.5)
+}
+// ANCHOR_END: catmull_rom
+
+impl CameraTrack {
+    // ANCHOR: camera_track_play
+    // Starts the playback, the track could be played again only after it has ended.
+    pub fn play(&mut self) {
+        if self.time.is_none() {
+            self.time = Some(0.0);
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.time.is_some()
+    }
+
+    // Switches the view between the player's camera and the camera of the track.
+    fn set_cutscene(&self, graph: &mut Graph, active: bool) {
+        let player_camera = graph
+            .try_get(self.player)
+            .and_then(|body| body.try_get_script::<Player>())
+            .map(|player| player.camera())
+            .unwrap_or_default();
+        with_player(graph, self.player, |player| player.set_cutscene(active));
+
+        if let Some(camera) = graph.try_get_mut(player_camera) {
+            camera.set_enabled(!active);
+        }
+        if let Some(camera) = graph.try_get_mut(*self.camera) {
+            camera.set_enabled(active);
+        }
+    }
+    // ANCHOR_END: camera_track_play
+
+    // ANCHOR: camera_track_sample
+    // Position and rotation of the camera at the given part of the track, in [0; 1] range.
+    fn sample(&self, graph: &Graph, progress: f32) -> Option<(Vector3<f32>, UnitQuaternion<f32>)> {
+        let points = self
+            .waypoints
+            .iter()
+            .filter_map(|waypoint| graph.try_get(*waypoint))
+            .map(|waypoint| (waypoint.global_position(), waypoint.global_rotation()))
+            .collect::<Vec<_>>();
+        let last = points.len().checked_sub(1)?;
+        if last == 0 {
+            return points.first().cloned();
+        }
+
+        let scaled = progress.clamp(0.0, 1.0) * last as f32;
+        let segment = (scaled as usize).min(last - 1);
+        let mut t = scaled - segment as f32;
+        if *self.easing == CameraTrackEasing::Smooth {
+            t = t * t * (3.0 - 2.0 * t);
+        }
+
+        // The first and the last points have no neighbours, so they're repeated.
+        let point = |i: usize| points[i.min(last)].0;
+        let position = catmull_rom(
+            point(segment.saturating_sub(1)),
+            point(segment),
+            point(segment + 1),
+            point(segment + 2),
+            t,
+        );
+        let rotation = points[segment].1.slerp(&points[segment + 1].1, t);
+        Some((position, rotation))
+    }
+    // ANCHOR_END: camera_track_sample
+}
+
+impl TriggerHandler for CameraTrack {
+    fn trigger_volume(&mut self) -> &mut TriggerVolume {
+        &mut self.trigger
+    }
+
+    fn on_enter(&mut self, _player: Handle<Node>, _ctx: &mut ScriptContext) {
+        if *self.play_on_enter && !self.played {
+            self.play();
+        }
+    }
+}
+
+impl ScriptTrait for CameraTrack {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        // The track camera must not show anything until the playback.
+        if let Some(camera) = ctx.scene.graph.try_get_mut(*self.camera) {
+            camera.set_enabled(false);
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if ctx.plugins.get::<Game>().paused {
+            return;
+        }
+
+        update_trigger(self, ctx);
+
+        // ANCHOR: camera_track_update
+        let Some(time) = self.time else {
+            return;
+        };
+
+        let graph = &mut ctx.scene.graph;
+        if time == 0.0 {
+            // The track could be started by another script, so the player is searched in the
+            // scene.
+            self.player = graph
+                .pair_iter()
+                .find(|(_, node)| node.has_script::<Player>())
+                .map_or(Handle::NONE, |(handle, _)| handle);
+            self.played = true;
+            self.set_cutscene(graph, true);
+        }
+
+        let duration = self.duration.max(f32::EPSILON);
+        if let Some((position, rotation)) = self.sample(graph, time / duration) {
+            if let Some(camera) = graph.try_get_mut(*self.camera) {
+                camera
+                    .local_transform_mut()
+                    .set_position(position)
+                    .set_rotation(rotation);
+            }
+        }
+
+        if time >= duration {
+            self.time = None;
+            self.set_cutscene(graph, false);
+        } else {
+            self.time = Some(time + ctx.dt);
+        }
+        // ANCHOR_END: camera_track_update
+    }
+}
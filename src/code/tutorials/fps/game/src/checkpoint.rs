@@ -0,0 +1,66 @@
+use crate::{
+    player::Player,
+    sound::play_sound,
+    trigger::{update_trigger, TriggerHandler, TriggerVolume},
+};
+use fyrox::{
+    core::{
+        pool::Handle, reflect::prelude::*, type_traits::prelude::*, variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{node::Node, sound::SoundBufferResource},
+    script::{ScriptContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "96ae6f0c-a70c-473f-860b-bc4a24e53f00")]
+#[visit(optional)]
+pub struct Checkpoint {
+    // ANCHOR: checkpoint_fields
+    // A sound that is played when the player reaches the checkpoint.
+    sound: InheritableVariable<Option<SoundBufferResource>>,
+
+    // Every checkpoint could be activated only once.
+    #[reflect(hidden)]
+    activated: bool,
+
+    #[reflect(hidden)]
+    trigger: TriggerVolume,
+    // ANCHOR_END: checkpoint_fields
+}
+
+impl TriggerHandler for Checkpoint {
+    fn trigger_volume(&mut self) -> &mut TriggerVolume {
+        &mut self.trigger
+    }
+
+    fn on_enter(&mut self, body: Handle<Node>, ctx: &mut ScriptContext) {
+        // ANCHOR: checkpoint_activation
+        let graph = &mut ctx.scene.graph;
+        let position = graph[body].global_position();
+        if let Some(player) = graph[body].try_get_script_mut::<Player>() {
+            player.set_checkpoint(position);
+        }
+
+        if let Some(sound) = self.sound.as_ref() {
+            let position = graph[ctx.handle].global_position();
+            play_sound(graph, sound, position, 1.0);
+        }
+
+        // Disable the trigger, so it won't check for the player anymore.
+        self.activated = true;
+        let children = graph[ctx.handle].children().to_vec();
+        for child in children {
+            graph[child].set_enabled(false);
+        }
+        // ANCHOR_END: checkpoint_activation
+    }
+}
+
+impl ScriptTrait for Checkpoint {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if !self.activated {
+            update_trigger(self, ctx);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{
+    interaction::{handle_interact_message, InteractMessage, Interactable, Interaction},
+    item::ItemId,
+    player::Player,
+    sound::play_sound,
+};
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{graph::Graph, node::Node, sound::SoundBufferResource},
+    script::{ScriptContext, ScriptMessageContext, ScriptMessagePayload, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "fb4dfbb3-5b1d-47d7-a8b6-85dee5295376")]
+#[visit(optional)]
+pub struct Door {
+    // ANCHOR: door_fields
+    #[component(include)]
+    interaction: Interaction,
+
+    // The door opens by itself when the player comes closer than this. Zero means that the door
+    // opens only by the interaction key.
+    auto_open_distance: InheritableVariable<f32>,
+
+    // Offset and rotation (in degrees around the vertical axis) of the fully open door relative
+    // to its closed state. Sliding doors use the offset, swinging doors use the angle.
+    open_offset: InheritableVariable<Vector3<f32>>,
+    open_angle: InheritableVariable<f32>,
+
+    // Time (in seconds) that the door needs to fully open or close.
+    open_time: InheritableVariable<f32>,
+
+    // Time (in seconds) after which an open door closes by itself.
+    close_delay: InheritableVariable<f32>,
+
+    // An item that the player must have to open the door, usually a key. There's no lock if it
+    // is not set.
+    required_item: InheritableVariable<Option<ItemId>>,
+
+    // The prompt that is shown instead of the usual one, when the player does not have the key.
+    locked_prompt: InheritableVariable<String>,
+
+    open_sound: InheritableVariable<Option<SoundBufferResource>>,
+    close_sound: InheritableVariable<Option<SoundBufferResource>>,
+    locked_sound: InheritableVariable<Option<SoundBufferResource>>,
+
+    // Where the door is going, it moves towards this state every frame.
+    #[reflect(hidden)]
+    is_open: bool,
+
+    // How much the door is open, from 0 (closed) to 1 (open).
+    #[reflect(hidden)]
+    open_factor: f32,
+
+    #[reflect(hidden)]
+    close_timer: f32,
+
+    // The usual prompt, it is restored when the player gets the key.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    unlocked_prompt: String,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    closed_position: Vector3<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    closed_rotation: UnitQuaternion<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    player: Handle<Node>,
+    // ANCHOR_END: door_fields
+}
+
+impl Default for Door {
+    fn default() -> Self {
+        Self {
+            interaction: Interaction {
+                prompt: "Press E to open".to_string(),
+            },
+            auto_open_distance: 0.0.into(),
+            open_offset: Default::default(),
+            open_angle: 90.0.into(),
+            open_time: 1.0.into(),
+            close_delay: 3.0.into(),
+            required_item: Default::default(),
+            locked_prompt: "Locked".to_string().into(),
+            open_sound: Default::default(),
+            close_sound: Default::default(),
+            locked_sound: Default::default(),
+            is_open: false,
+            open_factor: 0.0,
+            close_timer: 0.0,
+            unlocked_prompt: Default::default(),
+            closed_position: Default::default(),
+            closed_rotation: Default::default(),
+            player: Default::default(),
+        }
+    }
+}
+
+impl Door {
+    // ANCHOR: door_lock
+    fn can_open(&self, player: Option<&Player>) -> bool {
+        self.required_item.map_or(true, |item| {
+            player.map_or(false, |player| player.has_item(item))
+        })
+    }
+    // ANCHOR_END: door_lock
+
+    // ANCHOR: door_open
+    // Starts opening or closing the door, the sound is played only when the state changes.
+    fn set_open(&mut self, graph: &mut Graph, handle: Handle<Node>, open: bool) {
+        self.close_timer = *self.close_delay;
+        if self.is_open == open {
+            return;
+        }
+        self.is_open = open;
+
+        let sound = if open {
+            self.open_sound.as_ref()
+        } else {
+            self.close_sound.as_ref()
+        };
+        if let Some(sound) = sound {
+            let position = graph[handle].global_position();
+            play_sound(graph, sound, position, 1.0);
+        }
+    }
+    // ANCHOR_END: door_open
+}
+
+// ANCHOR: door_interaction
+impl Interactable for Door {
+    fn interact(&mut self, player: Handle<Node>, ctx: &mut ScriptMessageContext) {
+        let graph = &mut ctx.scene.graph;
+        let can_open = self.can_open(
+            graph
+                .try_get(player)
+                .and_then(|node| node.try_get_script::<Player>()),
+        );
+
+        if can_open {
+            self.set_open(graph, ctx.handle, !self.is_open);
+        } else if let Some(sound) = self.locked_sound.as_ref() {
+            let position = graph[ctx.handle].global_position();
+            play_sound(graph, sound, position, 1.0);
+        }
+    }
+}
+// ANCHOR_END: door_interaction
+
+impl ScriptTrait for Door {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        // ANCHOR: door_on_start
+        let transform = ctx.scene.graph[ctx.handle].local_transform();
+        self.closed_position = **transform.position();
+        self.closed_rotation = **transform.rotation();
+        self.unlocked_prompt = self.interaction.prompt.clone();
+
+        self.player = ctx
+            .scene
+            .graph
+            .pair_iter()
+            .find(|(_, node)| node.has_script::<Player>())
+            .map(|(handle, _)| handle)
+            .unwrap_or_default();
+
+        ctx.message_dispatcher
+            .subscribe_to::<InteractMessage>(ctx.handle);
+        // ANCHOR_END: door_on_start
+    }
+
+    fn on_message(
+        &mut self,
+        message: &mut dyn ScriptMessagePayload,
+        ctx: &mut ScriptMessageContext,
+    ) {
+        handle_interact_message(self, message, ctx);
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        // ANCHOR: door_auto_open
+        let graph = &mut ctx.scene.graph;
+        let position = graph[ctx.handle].global_position();
+        let (player_near, can_open) = match graph.try_get(self.player) {
+            Some(player) => (
+                *self.auto_open_distance > 0.0
+                    && (player.global_position() - position).norm() <= *self.auto_open_distance,
+                self.can_open(player.try_get_script::<Player>()),
+            ),
+            None => (false, self.can_open(None)),
+        };
+
+        // Locked doors show another prompt, this way the player knows that a key is needed.
+        let prompt = if can_open {
+            &self.unlocked_prompt
+        } else {
+            &*self.locked_prompt
+        };
+        if self.interaction.prompt != *prompt {
+            self.interaction.prompt = prompt.clone();
+        }
+
+        if player_near && can_open {
+            self.set_open(graph, ctx.handle, true);
+        } else if self.is_open {
+            self.close_timer -= ctx.dt;
+            if self.close_timer <= 0.0 {
+                self.set_open(graph, ctx.handle, false);
+            }
+        }
+        // ANCHOR_END: door_auto_open
+
+        // ANCHOR: door_animation
+        let step = ctx.dt / self.open_time.max(f32::EPSILON);
+        self.open_factor = if self.is_open {
+            (self.open_factor + step).min(1.0)
+        } else {
+            (self.open_factor - step).max(0.0)
+        };
+
+        // The movement starts and stops smoothly.
+        let t = self.open_factor * self.open_factor * (3.0 - 2.0 * self.open_factor);
+        let transform = graph[ctx.handle].local_transform_mut();
+        transform.set_position(self.closed_position + self.open_offset.scale(t));
+        transform.set_rotation(
+            self.closed_rotation
+                * UnitQuaternion::from_axis_angle(
+                    &Vector3::y_axis(),
+                    (*self.open_angle * t).to_radians(),
+                ),
+        );
+        // ANCHOR_END: door_animation
+    }
+}
@@ -0,0 +1,33 @@
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+    },
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{node::Node, Scene},
+};
+
+// Effects are removed after this time (in seconds), unless their prefab has its own lifetime.
+const DEFAULT_EFFECT_LIFETIME: f32 = 3.0;
+
+// ANCHOR: spawn_effect
+// Instantiates a prefab of an effect (usually a particle system) that removes itself when it is
+// finished. Nothing happens, if there's no prefab.
+pub fn spawn_effect(
+    scene: &mut Scene,
+    prefab: Option<&ModelResource>,
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+) -> Handle<Node> {
+    let Some(prefab) = prefab else {
+        return Handle::NONE;
+    };
+
+    let effect = prefab.instantiate_at(scene, position, rotation);
+    let node = &mut scene.graph[effect];
+    if node.lifetime().is_none() {
+        node.set_lifetime(Some(DEFAULT_EFFECT_LIFETIME));
+    }
+    effect
+}
+// ANCHOR_END: spawn_effect
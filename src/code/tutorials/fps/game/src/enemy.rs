@@ -0,0 +1,460 @@
+use crate::{health::find_body_health, player::Player, trigger::with_player, Game};
+use fyrox::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    rand::{thread_rng, Rng},
+    scene::{
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
+        navmesh::NavigationalMesh,
+        node::Node,
+        rigidbody::RigidBody,
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+
+// Horizontal distance at which a waypoint is considered reached.
+const WAYPOINT_REACH_DISTANCE: f32 = 0.5;
+
+// Height of the enemy's eyes relative to the origin of its body.
+const EYE_HEIGHT: f32 = 0.5;
+
+// Trauma that each shot adds to the screen shake of the player.
+const SHOT_SHAKE: f32 = 0.15;
+
+// Height of the health bar above the origin of the body.
+const HEALTH_BAR_HEIGHT: f32 = 1.3;
+
+// Returns an offset between two points in the XZ plane.
+fn horizontal_offset(from: Vector3<f32>, to: Vector3<f32>) -> Vector3<f32> {
+    Vector3::new(to.x - from.x, 0.0, to.z - from.z)
+}
+
+// ANCHOR: enemy_state
+#[derive(Visit, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnemyState {
+    #[default]
+    Patrol,
+    Chase,
+}
+// ANCHOR_END: enemy_state
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "9f6cbf4a-91c9-4a68-9491-c51959106741")]
+#[visit(optional)]
+pub struct Enemy {
+    // ANCHOR: enemy_fields
+    // Points that the enemy walks between, the list is looped.
+    waypoints: InheritableVariable<Vec<Handle<Node>>>,
+
+    // Horizontal speed of the enemy in meters per second.
+    move_speed: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    current_waypoint: usize,
+    // ANCHOR_END: enemy_fields
+
+    // ANCHOR: sight_fields
+    // Maximum distance at which the enemy can see the player.
+    sight_range: InheritableVariable<f32>,
+
+    // Time (in seconds) after which the enemy gives up the chase, if it cannot see the player.
+    sight_timeout: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    state: EnemyState,
+
+    #[reflect(hidden)]
+    lost_sight_timer: f32,
+
+    // Result of the last line of sight check.
+    #[reflect(hidden)]
+    player_visible: bool,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    player: Handle<Node>,
+    // ANCHOR_END: sight_fields
+
+    // ANCHOR: navigation_fields
+    // A navigational mesh that is used to find paths around obstacles.
+    navmesh: InheritableVariable<Handle<Node>>,
+
+    // Time (in seconds) between path recalculations.
+    path_update_interval: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    path_timer: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    path: Vec<Vector3<f32>>,
+    // ANCHOR_END: navigation_fields
+
+    // ANCHOR: attack_fields
+    // Maximum distance at which the enemy shoots at the player.
+    attack_range: InheritableVariable<f32>,
+
+    // How many shots per second the enemy makes.
+    fire_rate: InheritableVariable<f32>,
+
+    // Amount of health that a single shot takes from the player.
+    damage: InheritableVariable<f32>,
+
+    // Chance (from 0 to 1) of a shot to hit the player.
+    accuracy: InheritableVariable<f32>,
+
+    // Time (in seconds) between spotting the player and the first shot, the player has a moment
+    // to react.
+    telegraph_delay: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    attack_timer: f32,
+
+    #[reflect(hidden)]
+    is_attacking: bool,
+    // ANCHOR_END: attack_fields
+
+    // ANCHOR: health_bar_fields
+    // Health at the start, it is used to show how much health is left.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    max_health: f32,
+    // ANCHOR_END: health_bar_fields
+
+    // ANCHOR: score_value_field
+    // Amount of points that the player gets for killing the enemy.
+    score_value: InheritableVariable<u32>,
+    // ANCHOR_END: score_value_field
+}
+
+impl Default for Enemy {
+    fn default() -> Self {
+        Self {
+            waypoints: Default::default(),
+            move_speed: 2.0.into(),
+            current_waypoint: 0,
+            sight_range: 15.0.into(),
+            sight_timeout: 3.0.into(),
+            state: Default::default(),
+            lost_sight_timer: 0.0,
+            player_visible: false,
+            player: Default::default(),
+            navmesh: Default::default(),
+            path_update_interval: 0.5.into(),
+            path_timer: 0.0,
+            path: Default::default(),
+            attack_range: 12.0.into(),
+            fire_rate: 1.5.into(),
+            damage: 10.0.into(),
+            accuracy: 0.6.into(),
+            telegraph_delay: 0.75.into(),
+            attack_timer: 0.0,
+            is_attacking: false,
+            max_health: 0.0,
+            score_value: 100.into(),
+        }
+    }
+}
+
+impl Enemy {
+    // ANCHOR: is_dead
+    // Health script is assigned either to the enemy or to its collider, this way player's shots
+    // could find it.
+    fn is_dead(&self, ctx: &ScriptContext) -> bool {
+        find_body_health(&ctx.scene.graph, ctx.handle).map_or(false, |health| health.is_dead())
+    }
+    // ANCHOR_END: is_dead
+
+    // ANCHOR: health_bar
+    fn health(graph: &Graph, handle: Handle<Node>) -> Option<f32> {
+        find_body_health(graph, handle).map(|health| health.hp)
+    }
+
+    pub fn is_alive(graph: &Graph, handle: Handle<Node>) -> bool {
+        Self::health(graph, handle).map_or(false, |health| health > 0.0)
+    }
+
+    // Returns a world position of the health bar and a fraction of health that is left. There's no
+    // bar when the enemy is not damaged.
+    pub fn health_bar(&self, graph: &Graph, handle: Handle<Node>) -> Option<(Vector3<f32>, f32)> {
+        let health = Self::health(graph, handle)?;
+        if self.max_health <= 0.0 || health >= self.max_health {
+            return None;
+        }
+        let position = graph[handle].global_position() + Vector3::new(0.0, HEALTH_BAR_HEIGHT, 0.0);
+        Some((position, health / self.max_health))
+    }
+    // ANCHOR_END: health_bar
+
+    // ANCHOR: patrol
+    // Returns a position of the current waypoint, switching to the next one when the current
+    // is reached.
+    fn patrol(&mut self, ctx: &ScriptContext) -> Option<Vector3<f32>> {
+        if self.waypoints.is_empty() {
+            return None;
+        }
+
+        let graph = &ctx.scene.graph;
+        let position = graph[ctx.handle].global_position();
+
+        self.current_waypoint %= self.waypoints.len();
+        let waypoint = graph
+            .try_get(self.waypoints[self.current_waypoint])?
+            .global_position();
+
+        if horizontal_offset(position, waypoint).norm() <= WAYPOINT_REACH_DISTANCE {
+            self.current_waypoint = (self.current_waypoint + 1) % self.waypoints.len();
+            // The path leads to the old waypoint, so it must be rebuilt.
+            self.path_timer = 0.0;
+            return None;
+        }
+
+        Some(waypoint)
+    }
+    // ANCHOR_END: patrol
+
+    // ANCHOR: can_see_player
+    // The player is visible, if it is close enough and the first thing that a ray from the eyes
+    // of the enemy hits is the player.
+    fn can_see_player(&self, ctx: &ScriptContext) -> bool {
+        let graph = &ctx.scene.graph;
+        let Some(player) = graph.try_get(self.player) else {
+            return false;
+        };
+
+        let eyes = graph[ctx.handle].global_position() + Vector3::new(0.0, EYE_HEIGHT, 0.0);
+        let offset = player.global_position() - eyes;
+        let distance = offset.norm();
+        if distance > *self.sight_range {
+            return false;
+        }
+
+        let mut intersections = Vec::new();
+        graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::from(eyes),
+                ray_direction: offset,
+                max_len: distance,
+                groups: Default::default(),
+                sort_results: true,
+            },
+            &mut intersections,
+        );
+
+        // Colliders of the enemy itself must be ignored, the ray starts inside of them.
+        let first_hit = intersections
+            .into_iter()
+            .find(|intersection: &Intersection| {
+                graph
+                    .try_get(intersection.collider)
+                    .map_or(false, |collider| collider.parent() != ctx.handle)
+            });
+
+        first_hit.map_or(false, |intersection| {
+            intersection.collider == self.player
+                || graph[intersection.collider].parent() == self.player
+        })
+    }
+    // ANCHOR_END: can_see_player
+
+    // ANCHOR: update_state
+    fn update_state(&mut self, ctx: &ScriptContext) {
+        let prev_state = self.state;
+
+        self.player_visible = self.can_see_player(ctx);
+        if self.player_visible {
+            self.state = EnemyState::Chase;
+            self.lost_sight_timer = 0.0;
+        } else if self.state == EnemyState::Chase {
+            self.lost_sight_timer += ctx.dt;
+            if self.lost_sight_timer >= *self.sight_timeout {
+                self.state = EnemyState::Patrol;
+            }
+        }
+
+        // The target has changed, so the current path is no longer valid.
+        if self.state != prev_state {
+            self.path_timer = 0.0;
+        }
+    }
+    // ANCHOR_END: update_state
+
+    // ANCHOR: chase
+    // Returns a position of the player.
+    fn chase(&self, ctx: &ScriptContext) -> Option<Vector3<f32>> {
+        ctx.scene
+            .graph
+            .try_get(self.player)
+            .map(|player| player.global_position())
+    }
+    // ANCHOR_END: chase
+
+    // ANCHOR: update_path
+    // Building a path is quite expensive, so it is done only from time to time.
+    fn update_path(&mut self, ctx: &ScriptContext, target: Vector3<f32>) {
+        self.path_timer -= ctx.dt;
+        if self.path_timer > 0.0 {
+            return;
+        }
+        self.path_timer = *self.path_update_interval;
+        self.path.clear();
+
+        let graph = &ctx.scene.graph;
+        let Some(navmesh_node) = graph.try_get_of_type::<NavigationalMesh>(*self.navmesh) else {
+            return;
+        };
+        let shared_navmesh = navmesh_node.navmesh();
+        let navmesh = shared_navmesh.read();
+
+        let position = graph[ctx.handle].global_position();
+        if let (Some((_, begin_index)), Some((_, end_index))) = (
+            navmesh.query_closest(position),
+            navmesh.query_closest(target),
+        ) {
+            if navmesh
+                .build_path(begin_index, end_index, &mut self.path)
+                .is_err()
+            {
+                self.path.clear();
+            }
+        }
+    }
+    // ANCHOR_END: update_path
+
+    // ANCHOR: follow_path
+    // Returns a horizontal direction to the next point of the path. When there's no path, the
+    // enemy moves straight to the target.
+    fn follow_path(&mut self, ctx: &ScriptContext, target: Vector3<f32>) -> Option<Vector3<f32>> {
+        let position = ctx.scene.graph[ctx.handle].global_position();
+
+        // Skip the points that are already reached.
+        let reached = self
+            .path
+            .iter()
+            .take_while(|point| {
+                horizontal_offset(position, **point).norm() <= WAYPOINT_REACH_DISTANCE
+            })
+            .count();
+        self.path.drain(..reached);
+
+        let next_point = self.path.first().cloned().unwrap_or(target);
+        horizontal_offset(position, next_point).try_normalize(f32::EPSILON)
+    }
+    // ANCHOR_END: follow_path
+
+    // ANCHOR: attack
+    fn update_attack(&mut self, ctx: &mut ScriptContext) {
+        let eyes =
+            ctx.scene.graph[ctx.handle].global_position() + Vector3::new(0.0, EYE_HEIGHT, 0.0);
+        let in_range = ctx
+            .scene
+            .graph
+            .try_get(self.player)
+            .map_or(false, |player| {
+                (player.global_position() - eyes).norm() <= *self.attack_range
+            });
+
+        // Firing stops as soon as the player hides, and the delay starts over when it shows up.
+        if !self.player_visible || !in_range {
+            self.is_attacking = false;
+            return;
+        }
+        if !self.is_attacking {
+            self.is_attacking = true;
+            self.attack_timer = *self.telegraph_delay;
+        }
+
+        self.attack_timer -= ctx.dt;
+        if self.attack_timer > 0.0 {
+            return;
+        }
+        self.attack_timer = 1.0 / self.fire_rate.max(f32::EPSILON);
+
+        // Shots shake the camera even if they miss.
+        let hit = thread_rng().gen_bool(self.accuracy.clamp(0.0, 1.0) as f64);
+        let damage = *self.damage;
+        with_player(&mut ctx.scene.graph, self.player, |player| {
+            if hit {
+                player.take_damage(damage, Some(eyes));
+            }
+            player.add_shake(SHOT_SHAKE);
+        });
+    }
+    // ANCHOR_END: attack
+}
+
+impl ScriptTrait for Enemy {
+    // ANCHOR: enemy_on_start
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.player = ctx
+            .scene
+            .graph
+            .pair_iter()
+            .find(|(_, node)| node.has_script::<Player>())
+            .map(|(handle, _)| handle)
+            .unwrap_or_default();
+
+        self.max_health = Self::health(&ctx.scene.graph, ctx.handle).unwrap_or_default();
+    }
+    // ANCHOR_END: enemy_on_start
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if ctx.plugins.get::<Game>().paused {
+            return;
+        }
+
+        // ANCHOR: enemy_time_scale
+        // All timers of the enemy use the time step, so slow motion slows them down too.
+        ctx.dt *= ctx.plugins.get::<Game>().time_scale;
+        // ANCHOR_END: enemy_time_scale
+
+        // ANCHOR: enemy_death
+        if self.is_dead(ctx) {
+            ctx.plugins.get_mut::<Game>().add_score(*self.score_value);
+            ctx.scene.graph.remove_node(ctx.handle);
+            return;
+        }
+        // ANCHOR_END: enemy_death
+
+        // ANCHOR: enemy_movement
+        self.update_state(ctx);
+
+        let target = match self.state {
+            EnemyState::Patrol => self.patrol(ctx),
+            EnemyState::Chase => self.chase(ctx),
+        };
+
+        let direction = target.and_then(|target| {
+            self.update_path(ctx, target);
+            self.follow_path(ctx, target)
+        });
+
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            let velocity = direction.unwrap_or_default().scale(*self.move_speed);
+            let y_vel = rigid_body.lin_vel().y;
+            rigid_body.set_lin_vel(Vector3::new(velocity.x, y_vel, velocity.z));
+
+            // Turn the enemy to face the direction it walks in.
+            if let Some(direction) = direction {
+                let angle = direction.x.atan2(direction.z);
+                rigid_body
+                    .local_transform_mut()
+                    .set_rotation(UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle));
+            }
+        }
+        // ANCHOR_END: enemy_movement
+
+        // ANCHOR: enemy_attack
+        self.update_attack(ctx);
+        // ANCHOR_END: enemy_attack
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{health::Health, player::Player};
+use fyrox::{
+    core::{
+        algebra::{Point3, Vector3},
+        color::Color,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::BaseBuilder,
+        graph::{physics::RayCastOptions, Graph},
+        light::{
+            point::{PointLight, PointLightBuilder},
+            BaseLightBuilder,
+        },
+        node::Node,
+        rigidbody::RigidBody,
+        transform::TransformBuilder,
+    },
+    script::{Script, ScriptContext, ScriptTrait},
+};
+
+// Explosions shake the camera of the player within this radius, the closer the stronger.
+const EXPLOSION_SHAKE_RADIUS: f32 = 10.0;
+const EXPLOSION_SHAKE: f32 = 0.8;
+
+// Impulse that every point of damage gives to a rigid body, so stronger explosions push harder.
+const IMPULSE_PER_DAMAGE: f32 = 0.2;
+
+// Speed (in m/s) that every point of damage gives to the player.
+const PLAYER_KNOCKBACK_PER_DAMAGE: f32 = 0.1;
+
+// Light of an explosion fades out during this time (in seconds).
+const FLASH_DURATION: f32 = 0.25;
+const FLASH_INTENSITY: f32 = 4.0;
+
+// ANCHOR: explode
+// Damages everything with health within the radius and pushes it away, the closer to the center
+// the stronger. If the occlusion is checked, anything between the center and a target protects
+// the target.
+pub fn explode(
+    ctx: &mut ScriptContext,
+    position: Vector3<f32>,
+    radius: f32,
+    damage: f32,
+    check_occlusion: bool,
+) {
+    let graph = &ctx.scene.graph;
+    let targets = graph
+        .pair_iter()
+        .filter(|(_, node)| node.has_script::<Health>() || node.has_script::<Player>())
+        .map(|(handle, node)| (handle, node.global_position()))
+        .filter(|(_, target)| radius > 0.0 && (target - position).norm() <= radius)
+        .filter(|(handle, target)| {
+            !check_occlusion || is_exposed(graph, position, *handle, *target)
+        })
+        .collect::<Vec<_>>();
+
+    for (handle, target) in targets {
+        let offset = target - position;
+        let scale = 1.0 - offset.norm() / radius;
+        let amount = damage * scale;
+        let direction = offset
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+
+        let graph = &mut ctx.scene.graph;
+        if let Some(player) = graph[handle].try_get_script_mut::<Player>() {
+            player.take_damage(amount, Some(position));
+            player.launch(direction.scale(amount * PLAYER_KNOCKBACK_PER_DAMAGE), true);
+            continue;
+        }
+        if let Some(health) = graph[handle].try_get_script_mut::<Health>() {
+            health.damage(amount);
+        }
+
+        // Health could be assigned to a collider, then its parent is the rigid body.
+        let body = if graph.try_get_of_type::<RigidBody>(handle).is_some() {
+            handle
+        } else {
+            graph[handle].parent()
+        };
+        if let Some(rigid_body) = graph.try_get_mut_of_type::<RigidBody>(body) {
+            rigid_body.apply_impulse(direction.scale(amount * IMPULSE_PER_DAMAGE));
+        }
+    }
+
+    // Explosions are felt farther than they hurt.
+    let shake_radius = EXPLOSION_SHAKE_RADIUS.max(radius * 2.0);
+    for node in ctx.scene.graph.linear_iter_mut() {
+        let distance = (node.global_position() - position).norm();
+        if let Some(player) = node.try_get_script_mut::<Player>() {
+            player.add_shake(EXPLOSION_SHAKE * (1.0 - distance / shake_radius));
+        }
+    }
+
+    spawn_flash(&mut ctx.scene.graph, position, radius.max(1.0) * 2.0);
+}
+
+// Returns true if nothing stands between the center of an explosion and the target.
+fn is_exposed(
+    graph: &Graph,
+    position: Vector3<f32>,
+    target: Handle<Node>,
+    target_position: Vector3<f32>,
+) -> bool {
+    let offset = target_position - position;
+    let Some(direction) = offset.try_normalize(f32::EPSILON) else {
+        return true;
+    };
+
+    let mut intersections = Vec::new();
+    graph.physics.cast_ray(
+        RayCastOptions {
+            ray_origin: Point3::from(position),
+            ray_direction: direction,
+            max_len: offset.norm(),
+            groups: Default::default(),
+            sort_results: true,
+        },
+        &mut intersections,
+    );
+
+    // The thing that exploded (a rocket, for example) could still have a collider at the center,
+    // so intersections right at the start of the ray are skipped.
+    intersections
+        .iter()
+        .find(|intersection| intersection.toi > f32::EPSILON)
+        .map_or(true, |intersection| {
+            intersection.collider == target
+                || graph
+                    .try_get(intersection.collider)
+                    .map_or(false, |collider| collider.parent() == target)
+        })
+}
+// ANCHOR_END: explode
+
+// ANCHOR: explosion_flash
+// A short flash of light that removes itself when it is faded out.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "bfcafe58-a1f4-4c74-8792-dbba29c7bec0")]
+#[visit(optional)]
+pub struct ExplosionFlash {
+    #[reflect(hidden)]
+    time: f32,
+}
+
+fn spawn_flash(graph: &mut Graph, position: Vector3<f32>, radius: f32) -> Handle<Node> {
+    PointLightBuilder::new(
+        BaseLightBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                )
+                .with_script(Script::new(ExplosionFlash::default())),
+        )
+        .with_color(Color::opaque(255, 180, 90))
+        .with_intensity(FLASH_INTENSITY),
+    )
+    .with_radius(radius)
+    .build(graph)
+}
+
+impl ScriptTrait for ExplosionFlash {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        self.time += ctx.dt;
+        if self.time >= FLASH_DURATION {
+            ctx.scene.graph.remove_node(ctx.handle);
+            return;
+        }
+
+        if let Some(light) = ctx
+            .scene
+            .graph
+            .try_get_mut_of_type::<PointLight>(ctx.handle)
+        {
+            light.set_intensity(FLASH_INTENSITY * (1.0 - self.time / FLASH_DURATION));
+        }
+    }
+}
+// ANCHOR_END: explosion_flash
@@ -0,0 +1,75 @@
+use fyrox::core::{algebra::Vector2, log::Log};
+use gilrs::{Axis, Gilrs};
+
+// Stick deflection below this value is treated as no input at all, this prevents worn sticks
+// from slowly rotating the camera or moving the player.
+const DEADZONE: f32 = 0.15;
+
+// ANCHOR: gamepad_state
+#[derive(Default, Debug, Clone)]
+pub struct GamepadState {
+    pub left_stick: Vector2<f32>,
+    pub right_stick: Vector2<f32>,
+}
+// ANCHOR_END: gamepad_state
+
+// Removes the deadzone from the stick value and rescales the rest of the range back to 0..1.
+fn apply_deadzone(value: Vector2<f32>) -> Vector2<f32> {
+    let length = value.norm();
+    if length <= DEADZONE {
+        Vector2::default()
+    } else {
+        value.scale(((length - DEADZONE) / (1.0 - DEADZONE)).min(1.0) / length)
+    }
+}
+
+// ANCHOR: gamepad_input
+pub struct GamepadInput {
+    gilrs: Option<Gilrs>,
+    pub state: GamepadState,
+}
+
+impl GamepadInput {
+    pub fn new() -> Self {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(err) => {
+                Log::err(format!(
+                    "Unable to initialize gamepad input. Reason: {err:?}"
+                ));
+                None
+            }
+        };
+
+        Self {
+            gilrs,
+            state: Default::default(),
+        }
+    }
+
+    pub fn update(&mut self) {
+        let Some(gilrs) = self.gilrs.as_mut() else {
+            return;
+        };
+
+        // Events must be pumped to keep the state of the gamepads up-to-date.
+        while gilrs.next_event().is_some() {}
+
+        // Only the first connected gamepad is used.
+        self.state = gilrs
+            .gamepads()
+            .next()
+            .map(|(_, gamepad)| GamepadState {
+                left_stick: apply_deadzone(Vector2::new(
+                    gamepad.value(Axis::LeftStickX),
+                    gamepad.value(Axis::LeftStickY),
+                )),
+                right_stick: apply_deadzone(Vector2::new(
+                    gamepad.value(Axis::RightStickX),
+                    gamepad.value(Axis::RightStickY),
+                )),
+            })
+            .unwrap_or_default();
+    }
+}
+// ANCHOR_END: gamepad_input
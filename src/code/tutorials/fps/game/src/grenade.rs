@@ -0,0 +1,81 @@
+use crate::{effect::spawn_effect, explosion::explode, Game};
+use fyrox::{
+    core::{
+        algebra::UnitQuaternion, reflect::prelude::*, type_traits::prelude::*,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    resource::model::ModelResource,
+    script::{ScriptContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "eceec781-3fcb-4392-9711-b78baecccce4")]
+#[visit(optional)]
+pub struct Grenade {
+    // ANCHOR: grenade_fields
+    // Time (in seconds) from the throw to the explosion.
+    fuse: InheritableVariable<f32>,
+
+    explosion_radius: InheritableVariable<f32>,
+
+    // Damage of the explosion at its center.
+    damage: InheritableVariable<f32>,
+
+    explosion_effect: InheritableVariable<Option<ModelResource>>,
+
+    // Time that the fuse burned in the hand of the player before the throw.
+    #[reflect(hidden)]
+    cooked: f32,
+
+    #[reflect(hidden)]
+    time_left: f32,
+    // ANCHOR_END: grenade_fields
+}
+
+impl Default for Grenade {
+    fn default() -> Self {
+        Self {
+            fuse: 3.0.into(),
+            explosion_radius: 5.0.into(),
+            damage: 80.0.into(),
+            explosion_effect: Default::default(),
+            cooked: 0.0,
+            time_left: 0.0,
+        }
+    }
+}
+
+impl Grenade {
+    // Must be called right after the grenade is spawned, before it starts.
+    pub fn cook(&mut self, time: f32) {
+        self.cooked = time;
+    }
+}
+
+impl ScriptTrait for Grenade {
+    fn on_start(&mut self, _ctx: &mut ScriptContext) {
+        // A grenade that was cooked for too long explodes right after the throw.
+        self.time_left = (*self.fuse - self.cooked).max(0.0);
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if ctx.plugins.get::<Game>().paused {
+            return;
+        }
+
+        // ANCHOR: grenade_fuse
+        self.time_left -= ctx.dt;
+        if self.time_left <= 0.0 {
+            let position = ctx.scene.graph[ctx.handle].global_position();
+            explode(ctx, position, *self.explosion_radius, *self.damage, true);
+            spawn_effect(
+                ctx.scene,
+                self.explosion_effect.as_ref(),
+                position,
+                UnitQuaternion::default(),
+            );
+            ctx.scene.graph.remove_node(ctx.handle);
+        }
+        // ANCHOR_END: grenade_fuse
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{
+    explosion::explode,
+    sound::play_sound,
+    trigger::{update_trigger, with_player, TriggerHandler, TriggerVolume},
+    Game,
+};
+use fyrox::{
+    core::{
+        pool::Handle, reflect::prelude::*, type_traits::prelude::*, variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{graph::Graph, node::Node, sound::SoundBufferResource},
+    script::{ScriptContext, ScriptTrait},
+};
+
+// ANCHOR: hazard
+// Something that hurts the player, for example spikes or lava. Solid hazards hurt on every touch,
+// if the hazard has sensor colliders as its children, it also works as a volume and hurts the
+// player constantly while the player is inside.
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "4630f92f-c979-43d9-afd6-87f71dcb78ad")]
+#[visit(optional)]
+pub struct Hazard {
+    // Amount of health that every touch takes from the player.
+    damage: InheritableVariable<f32>,
+
+    // ANCHOR: hazard_volume_fields
+    // Amount of health that is taken from the player every second inside the volume.
+    damage_per_second: InheritableVariable<f32>,
+
+    // Kills the player right away, deep lava or a bottomless pit, for example.
+    instant_kill: InheritableVariable<bool>,
+
+    // A sound that is played when the player gets hurt inside the volume.
+    hurt_sound: InheritableVariable<Option<SoundBufferResource>>,
+
+    // If set, the hazard explodes once the player enters its volume, like a mine. The explosion
+    // deals the damage of a touch at its center.
+    explosion_radius: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    trigger: TriggerVolume,
+
+    #[reflect(hidden)]
+    exploded: bool,
+
+    // Time until the next hurt sound and damage indicator.
+    #[reflect(hidden)]
+    hurt_timer: f32,
+    // ANCHOR_END: hazard_volume_fields
+}
+
+impl Default for Hazard {
+    fn default() -> Self {
+        Self {
+            damage: 10.0.into(),
+            damage_per_second: 20.0.into(),
+            instant_kill: false.into(),
+            hurt_sound: Default::default(),
+            explosion_radius: 0.0.into(),
+            trigger: Default::default(),
+            exploded: false,
+            hurt_timer: 0.0,
+        }
+    }
+}
+
+impl Hazard {
+    pub fn damage(&self) -> f32 {
+        *self.damage
+    }
+}
+
+// Hazard script could be assigned either to a collider or to its rigid body.
+pub fn find_hazard(graph: &Graph, collider: Handle<Node>) -> Option<&Hazard> {
+    let parent = graph.try_get(collider)?.parent();
+    [collider, parent].into_iter().find_map(|handle| {
+        graph
+            .try_get(handle)
+            .and_then(|node| node.try_get_script::<Hazard>())
+    })
+}
+// ANCHOR_END: hazard
+
+// ANCHOR: hazard_volume
+// Damage inside the volume is applied every frame, but the sound and the indicator would be too
+// noisy at that rate, so they're repeated with this interval (in seconds).
+const HURT_INTERVAL: f32 = 0.5;
+
+impl TriggerHandler for Hazard {
+    fn trigger_volume(&mut self) -> &mut TriggerVolume {
+        &mut self.trigger
+    }
+
+    fn on_enter(&mut self, _player: Handle<Node>, ctx: &mut ScriptContext) {
+        // The player gets hurt right away on entering.
+        self.hurt_timer = 0.0;
+
+        // ANCHOR: hazard_explosion
+        if *self.explosion_radius > 0.0 {
+            let position = ctx.scene.graph[ctx.handle].global_position();
+            explode(ctx, position, *self.explosion_radius, *self.damage, true);
+            ctx.scene.graph.remove_node(ctx.handle);
+            self.exploded = true;
+        }
+        // ANCHOR_END: hazard_explosion
+    }
+}
+
+impl Hazard {
+    fn hurt_player(&mut self, ctx: &mut ScriptContext) {
+        let player = self.trigger.player();
+        if player.is_none() {
+            return;
+        }
+
+        // Multiplying by the time step makes the damage independent of the frame rate.
+        let mut amount = *self.damage_per_second * ctx.dt;
+        let instant_kill = *self.instant_kill;
+
+        self.hurt_timer -= ctx.dt;
+        let hurt = self.hurt_timer <= 0.0;
+        let position = ctx.scene.graph[ctx.handle].global_position();
+        if hurt {
+            self.hurt_timer = HURT_INTERVAL;
+            if let Some(sound) = self.hurt_sound.as_ref() {
+                let player_position = ctx.scene.graph[player].global_position();
+                play_sound(&mut ctx.scene.graph, sound, player_position, 1.0);
+            }
+        }
+
+        with_player(&mut ctx.scene.graph, player, |player| {
+            if instant_kill {
+                amount = player.health();
+            }
+            player.take_damage(amount, hurt.then_some(position));
+        });
+    }
+}
+// ANCHOR_END: hazard_volume
+
+impl ScriptTrait for Hazard {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if ctx.plugins.get::<Game>().paused {
+            return;
+        }
+
+        // ANCHOR: hazard_on_update
+        update_trigger(self, ctx);
+        // The node is removed after an explosion.
+        if !self.exploded {
+            self.hurt_player(ctx);
+        }
+        // ANCHOR_END: hazard_on_update
+    }
+}
@@ -0,0 +1,58 @@
+use fyrox::{
+    core::{pool::Handle, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    scene::{graph::Graph, node::Node},
+    script::ScriptTrait,
+};
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "dffd683c-2c66-43f5-b78b-29681e8d4e96")]
+#[visit(optional)]
+pub struct Health {
+    // ANCHOR: hp_field
+    pub hp: f32,
+    // ANCHOR_END: hp_field
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self { hp: 100.0 }
+    }
+}
+
+impl Health {
+    // ANCHOR: damage
+    pub fn damage(&mut self, amount: f32) {
+        self.hp = (self.hp - amount).max(0.0);
+    }
+    // ANCHOR_END: damage
+
+    pub fn is_dead(&self) -> bool {
+        self.hp <= 0.0
+    }
+}
+
+impl ScriptTrait for Health {}
+
+// ANCHOR: find_health
+// Health script could be assigned either to a collider or to its rigid body. Things without
+// health cannot be hurt, so `None` is returned for them.
+pub fn find_health_mut(graph: &mut Graph, collider: Handle<Node>) -> Option<&mut Health> {
+    let parent = graph.try_get(collider)?.parent();
+    let owner = [collider, parent].into_iter().find(|handle| {
+        graph
+            .try_get(*handle)
+            .map_or(false, |node| node.has_script::<Health>())
+    })?;
+    graph[owner].try_get_script_mut::<Health>()
+}
+
+// Finds the health of a body, the script is either on the body itself or on one of its colliders.
+pub fn find_body_health(graph: &Graph, body: Handle<Node>) -> Option<&Health> {
+    let node = graph.try_get(body)?;
+    node.try_get_script::<Health>().or_else(|| {
+        node.children()
+            .iter()
+            .find_map(|child| graph.try_get(*child)?.try_get_script::<Health>())
+    })
+}
+// ANCHOR_END: find_health
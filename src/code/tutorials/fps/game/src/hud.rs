@@ -0,0 +1,1076 @@
+use crate::{item::ItemId, objective::ObjectiveMarkers, player::Player};
+use fyrox::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        pool::Handle,
+    },
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        canvas::CanvasBuilder,
+        grid::GridBuilder,
+        image::{ImageBuilder, ImageMessage},
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        vector_image::{Primitive, VectorImageBuilder, VectorImageMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+    resource::texture::TextureResource,
+    scene::camera::Camera,
+};
+
+// Size of the crosshair when there's no texture is just a small dot.
+const CROSSHAIR_DOT_SIZE: f32 = 4.0;
+const CROSSHAIR_TEXTURE_SIZE: f32 = 32.0;
+
+// Indicators of damage direction are arcs on a circle of this radius around the crosshair. Each arc
+// covers this many degrees and is drawn with the given number of line segments.
+const DAMAGE_INDICATOR_RADIUS: f32 = 120.0;
+const DAMAGE_INDICATOR_ARC: f32 = 40.0;
+const DAMAGE_INDICATOR_THICKNESS: f32 = 6.0;
+const DAMAGE_INDICATOR_SEGMENTS: usize = 8;
+// Only this many indicators are shown at once, the rest are skipped.
+const MAX_DAMAGE_INDICATORS: usize = 8;
+
+// Damage numbers float up by this many pixels during their lifetime.
+const DAMAGE_NUMBER_RISE: f32 = 40.0;
+const MAX_DAMAGE_NUMBERS: usize = 8;
+
+// Size of the health bars above enemies and the max number of them on the screen.
+const HEALTH_BAR_WIDTH: f32 = 60.0;
+const HEALTH_BAR_HEIGHT: f32 = 6.0;
+const MAX_HEALTH_BARS: usize = 16;
+
+// Size of the minimap in the bottom right corner, the markers on it and the max number of enemies
+// that are shown.
+const MINIMAP_SIZE: f32 = 150.0;
+const MINIMAP_MARKER_SIZE: f32 = 6.0;
+const MAX_MINIMAP_BLIPS: usize = 32;
+
+// The compass at the top of the screen shows this many degrees around the view direction.
+const COMPASS_WIDTH: f32 = 400.0;
+const COMPASS_HEIGHT: f32 = 24.0;
+const COMPASS_RANGE: f32 = 180.0;
+const COMPASS_LABEL_WIDTH: f32 = 30.0;
+const COMPASS_MARKER_SIZE: f32 = 8.0;
+const MAX_COMPASS_MARKERS: usize = 8;
+
+// Names of the directions on the compass and their angles in the same form as the yaw of the
+// player. North is the positive Z axis, and west is the positive X axis, because it is to the left.
+const COMPASS_DIRECTIONS: [(&str, f32); 8] = [
+    ("N", 0.0),
+    ("NW", 45.0),
+    ("W", 90.0),
+    ("SW", 135.0),
+    ("S", 180.0),
+    ("SE", -135.0),
+    ("E", -90.0),
+    ("NE", -45.0),
+];
+
+// Markers of objectives in the world, the ones that are off the screen are kept at this distance
+// from its edges.
+const OBJECTIVE_MARKER_SIZE: f32 = 12.0;
+const OBJECTIVE_MARKER_TEXT_WIDTH: f32 = 120.0;
+const OBJECTIVE_MARKER_MARGIN: f32 = 30.0;
+const MAX_OBJECTIVE_MARKERS: usize = 8;
+
+// ANCHOR: hud_struct
+pub struct Hud {
+    root: Handle<UiNode>,
+    crosshair: Handle<UiNode>,
+    screen_size: Vector2<f32>,
+    crosshair_texture: Option<TextureResource>,
+    // The crosshair grows with the spread of shots.
+    shown_crosshair_size: f32,
+    health_text: Handle<UiNode>,
+    stamina_text: Handle<UiNode>,
+    ammo_text: Handle<UiNode>,
+    wave_text: Handle<UiNode>,
+    score_text: Handle<UiNode>,
+    items_text: Handle<UiNode>,
+    interaction_text: Handle<UiNode>,
+    water_tint: Handle<UiNode>,
+    // A pool of the damage indicators, the unused ones are hidden.
+    damage_indicators: Vec<Handle<UiNode>>,
+    hit_marker: Handle<UiNode>,
+    // A pool of the damage numbers, just like the damage indicators.
+    damage_numbers: Vec<Handle<UiNode>>,
+    // Pairs of a background and a fill of each health bar.
+    health_bars: Vec<(Handle<UiNode>, Handle<UiNode>)>,
+    // The player is always in the center of the minimap, the heading shows where the player looks.
+    minimap_heading: Handle<UiNode>,
+    // A pool of the enemy blips on the minimap.
+    minimap_blips: Vec<Handle<UiNode>>,
+    // Labels of the directions, in the same order as `COMPASS_DIRECTIONS`.
+    compass_labels: Vec<Handle<UiNode>>,
+    // A pool of the markers of objectives on the compass.
+    compass_markers: Vec<Handle<UiNode>>,
+    // Pairs of a marker of an objective in the world and its text with the label and the distance.
+    objective_markers: Vec<(Handle<UiNode>, Handle<UiNode>)>,
+    // Values that are currently shown, the text is updated only when they change.
+    shown_health: Option<u32>,
+    shown_stamina: Option<u32>,
+    shown_ammo: Option<(u32, u32, u32)>,
+    shown_wave: Option<u32>,
+    shown_score: Option<(u32, u32)>,
+    shown_items: Vec<ItemId>,
+    shown_prompt: Option<String>,
+    shown_water_tint: bool,
+}
+// ANCHOR_END: hud_struct
+
+impl Hud {
+    // ANCHOR: hud_new
+    pub fn new(ui: &mut UserInterface) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let crosshair = ImageBuilder::new(
+            WidgetBuilder::new()
+                .with_width(CROSSHAIR_DOT_SIZE)
+                .with_height(CROSSHAIR_DOT_SIZE)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_background(Brush::Solid(Color::WHITE)),
+        )
+        .build(ctx);
+
+        let health_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(10.0))
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Bottom),
+        )
+        .build(ctx);
+
+        // Stamina is shown right above the health.
+        let stamina_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness {
+                    left: 10.0,
+                    top: 10.0,
+                    right: 10.0,
+                    bottom: 35.0,
+                })
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Bottom),
+        )
+        .build(ctx);
+
+        let ammo_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(10.0))
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Bottom),
+        )
+        .build(ctx);
+
+        // The wave is shown at the top of the screen under the compass, it stays hidden until the
+        // first wave.
+        let wave_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness {
+                    left: 10.0,
+                    top: 40.0,
+                    right: 10.0,
+                    bottom: 10.0,
+                })
+                .with_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .build(ctx);
+
+        let score_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(10.0))
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .build(ctx);
+
+        // Collected items are listed at the top left corner.
+        let items_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::uniform(10.0))
+                .with_horizontal_alignment(HorizontalAlignment::Left)
+                .with_vertical_alignment(VerticalAlignment::Top),
+        )
+        .build(ctx);
+
+        // The prompt is shown right below the crosshair.
+        let interaction_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_margin(Thickness::top(80.0))
+                .with_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .build(ctx);
+
+        // A translucent overlay that colors the screen when the player is in water.
+        let water_tint = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_background(Brush::Solid(Color::from_rgba(20, 70, 120, 100))),
+        )
+        .build(ctx);
+
+        let damage_indicators = (0..MAX_DAMAGE_INDICATORS)
+            .map(|_| {
+                // Each indicator covers the whole circle, so the arc could be drawn at any angle.
+                VectorImageBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(DAMAGE_INDICATOR_RADIUS * 2.0)
+                        .with_height(DAMAGE_INDICATOR_RADIUS * 2.0)
+                        .with_visibility(false)
+                        .with_foreground(Brush::Solid(Color::RED)),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+        let damage_numbers = (0..MAX_DAMAGE_NUMBERS)
+            .map(|_| TextBuilder::new(WidgetBuilder::new().with_visibility(false)).build(ctx))
+            .collect::<Vec<_>>();
+        let health_bars = (0..MAX_HEALTH_BARS)
+            .map(|_| {
+                let fill = BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_horizontal_alignment(HorizontalAlignment::Left)
+                        .with_width(HEALTH_BAR_WIDTH)
+                        .with_background(Brush::Solid(Color::RED)),
+                )
+                .build(ctx);
+                let background = BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(HEALTH_BAR_WIDTH)
+                        .with_height(HEALTH_BAR_HEIGHT)
+                        .with_visibility(false)
+                        .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 160)))
+                        .with_child(fill),
+                )
+                .build(ctx);
+                (background, fill)
+            })
+            .collect::<Vec<_>>();
+
+        // ANCHOR: objective_markers_new
+        // The text is under the icon, the icon is at the origin of the marker.
+        let objective_markers = (0..MAX_OBJECTIVE_MARKERS)
+            .map(|_| {
+                let icon = BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(OBJECTIVE_MARKER_SIZE)
+                        .with_height(OBJECTIVE_MARKER_SIZE)
+                        .with_background(Brush::Solid(Color::opaque(255, 200, 0))),
+                )
+                .build(ctx);
+                let text = TextBuilder::new(
+                    WidgetBuilder::new()
+                        .with_desired_position(Vector2::new(
+                            (OBJECTIVE_MARKER_SIZE - OBJECTIVE_MARKER_TEXT_WIDTH) * 0.5,
+                            OBJECTIVE_MARKER_SIZE,
+                        ))
+                        .with_width(OBJECTIVE_MARKER_TEXT_WIDTH)
+                        .with_foreground(Brush::Solid(Color::opaque(255, 200, 0))),
+                )
+                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                .build(ctx);
+                let marker = CanvasBuilder::new(
+                    WidgetBuilder::new()
+                        .with_visibility(false)
+                        .with_child(icon)
+                        .with_child(text),
+                )
+                .build(ctx);
+                (marker, text)
+            })
+            .collect::<Vec<_>>();
+        // ANCHOR_END: objective_markers_new
+
+        // Canvas allows to place the indicators, the numbers and the bars at arbitrary positions.
+        let damage_indicator_canvas = CanvasBuilder::new(
+            WidgetBuilder::new()
+                .with_children(objective_markers.iter().map(|(marker, _)| *marker))
+                .with_children(health_bars.iter().map(|(background, _)| *background))
+                .with_children(damage_indicators.iter().cloned())
+                .with_children(damage_numbers.iter().cloned()),
+        )
+        .build(ctx);
+
+        // ANCHOR: minimap_new
+        // All markers are placed in the center at first, the player always stays there.
+        let minimap_marker = |ctx: &mut BuildContext, color: Color, visible: bool| {
+            BorderBuilder::new(
+                WidgetBuilder::new()
+                    .with_width(MINIMAP_MARKER_SIZE)
+                    .with_height(MINIMAP_MARKER_SIZE)
+                    .with_desired_position(Vector2::repeat(
+                        (MINIMAP_SIZE - MINIMAP_MARKER_SIZE) * 0.5,
+                    ))
+                    .with_visibility(visible)
+                    .with_background(Brush::Solid(color)),
+            )
+            .build(ctx)
+        };
+        let minimap_player = minimap_marker(ctx, Color::WHITE, true);
+        let minimap_heading = minimap_marker(ctx, Color::from_rgba(255, 255, 255, 120), true);
+        let minimap_blips = (0..MAX_MINIMAP_BLIPS)
+            .map(|_| minimap_marker(ctx, Color::RED, false))
+            .collect::<Vec<_>>();
+
+        // The minimap is placed right above the ammo.
+        let minimap = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(MINIMAP_SIZE)
+                .with_height(MINIMAP_SIZE)
+                .with_margin(Thickness {
+                    left: 10.0,
+                    top: 10.0,
+                    right: 10.0,
+                    bottom: 40.0,
+                })
+                .with_horizontal_alignment(HorizontalAlignment::Right)
+                .with_vertical_alignment(VerticalAlignment::Bottom)
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 120)))
+                .with_child(
+                    CanvasBuilder::new(
+                        WidgetBuilder::new()
+                            .with_clip_to_bounds(true)
+                            .with_children(minimap_blips.iter().cloned())
+                            .with_child(minimap_heading)
+                            .with_child(minimap_player),
+                    )
+                    .build(ctx),
+                ),
+        )
+        .build(ctx);
+        // ANCHOR_END: minimap_new
+
+        // ANCHOR: compass_new
+        let compass_labels = COMPASS_DIRECTIONS
+            .iter()
+            .map(|(name, _)| {
+                TextBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(COMPASS_LABEL_WIDTH)
+                        .with_height(COMPASS_HEIGHT)
+                        .with_visibility(false),
+                )
+                .with_text(*name)
+                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+        let compass_markers = (0..MAX_COMPASS_MARKERS)
+            .map(|_| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(COMPASS_MARKER_SIZE)
+                        .with_height(COMPASS_MARKER_SIZE)
+                        .with_visibility(false)
+                        .with_background(Brush::Solid(Color::opaque(255, 200, 0))),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let compass = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(COMPASS_WIDTH)
+                .with_height(COMPASS_HEIGHT)
+                .with_margin(Thickness::uniform(10.0))
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Top)
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 120)))
+                .with_child(
+                    CanvasBuilder::new(
+                        WidgetBuilder::new()
+                            .with_clip_to_bounds(true)
+                            .with_children(compass_labels.iter().cloned())
+                            .with_children(compass_markers.iter().cloned()),
+                    )
+                    .build(ctx),
+                ),
+        )
+        .build(ctx);
+        // ANCHOR_END: compass_new
+
+        // Hit marker is shown right over the crosshair.
+        let hit_marker = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text("X")
+        .build(ctx);
+
+        // The root covers the entire screen, this way its children could be aligned relative
+        // to the screen borders. It is hidden until the gameplay scene is loaded.
+        let root = GridBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_child(damage_indicator_canvas)
+                .with_child(hit_marker)
+                .with_child(water_tint)
+                .with_child(crosshair)
+                .with_child(minimap)
+                .with_child(compass)
+                .with_child(health_text)
+                .with_child(stamina_text)
+                .with_child(ammo_text)
+                .with_child(wave_text)
+                .with_child(score_text)
+                .with_child(items_text)
+                .with_child(interaction_text),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            crosshair,
+            screen_size: Vector2::default(),
+            crosshair_texture: None,
+            shown_crosshair_size: 0.0,
+            health_text,
+            stamina_text,
+            ammo_text,
+            shown_health: None,
+            shown_stamina: None,
+            shown_ammo: None,
+            wave_text,
+            shown_wave: None,
+            score_text,
+            shown_score: None,
+            items_text,
+            shown_items: Default::default(),
+            interaction_text,
+            shown_prompt: None,
+            water_tint,
+            shown_water_tint: false,
+            damage_indicators,
+            hit_marker,
+            damage_numbers,
+            health_bars,
+            minimap_heading,
+            minimap_blips,
+            compass_labels,
+            compass_markers,
+            objective_markers,
+        }
+    }
+    // ANCHOR_END: hud_new
+
+    pub fn set_visible(&mut self, ui: &mut UserInterface, visible: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+
+    // ANCHOR: hud_update
+    pub fn update(&mut self, ui: &mut UserInterface, player: &Player, camera: Option<&Camera>) {
+        self.update_stats(ui, player);
+        self.update_interaction_prompt(ui, player);
+        self.update_water_tint(ui, player);
+        self.update_items(ui, player);
+        self.update_damage_indicators(ui, player);
+        self.update_hit_marker(ui, player);
+        self.update_damage_numbers(ui, player, camera);
+
+        // Keep the root of the HUD as large as the screen.
+        let screen_size = ui.screen_size();
+        if screen_size != self.screen_size {
+            self.screen_size = screen_size;
+            ui.send_message(WidgetMessage::width(
+                self.root,
+                MessageDirection::ToWidget,
+                screen_size.x,
+            ));
+            ui.send_message(WidgetMessage::height(
+                self.root,
+                MessageDirection::ToWidget,
+                screen_size.y,
+            ));
+        }
+
+        let crosshair_texture = player.crosshair_texture();
+        if crosshair_texture != self.crosshair_texture.as_ref() {
+            self.crosshair_texture = crosshair_texture.cloned();
+            ui.send_message(ImageMessage::texture(
+                self.crosshair,
+                MessageDirection::ToWidget,
+                self.crosshair_texture.clone().map(Into::into),
+            ));
+        }
+
+        // ANCHOR: crosshair_spread
+        // The spread cone covers a circle on the screen, the crosshair is expanded to its size.
+        let base_size = if self.crosshair_texture.is_some() {
+            CROSSHAIR_TEXTURE_SIZE
+        } else {
+            CROSSHAIR_DOT_SIZE
+        };
+        let size = (base_size + player.spread_on_screen() * screen_size.y).round();
+        if size != self.shown_crosshair_size {
+            self.shown_crosshair_size = size;
+            ui.send_message(WidgetMessage::width(
+                self.crosshair,
+                MessageDirection::ToWidget,
+                size,
+            ));
+            ui.send_message(WidgetMessage::height(
+                self.crosshair,
+                MessageDirection::ToWidget,
+                size,
+            ));
+        }
+        // ANCHOR_END: crosshair_spread
+    }
+    // ANCHOR_END: hud_update
+
+    // ANCHOR: hud_stats
+    fn update_stats(&mut self, ui: &mut UserInterface, player: &Player) {
+        let health = player.health().ceil() as u32;
+        if self.shown_health != Some(health) {
+            self.shown_health = Some(health);
+            ui.send_message(TextMessage::text(
+                self.health_text,
+                MessageDirection::ToWidget,
+                format!("Health: {health}"),
+            ));
+        }
+
+        let stamina = player.stamina().ceil() as u32;
+        if self.shown_stamina != Some(stamina) {
+            self.shown_stamina = Some(stamina);
+            ui.send_message(TextMessage::text(
+                self.stamina_text,
+                MessageDirection::ToWidget,
+                format!("Stamina: {stamina}"),
+            ));
+        }
+
+        let (ammo, magazine_size) = player
+            .current_weapon()
+            .map(|weapon| (weapon.ammo, weapon.magazine_size))
+            .unwrap_or_default();
+        let ammo = (ammo, magazine_size, player.grenade_count);
+        if self.shown_ammo != Some(ammo) {
+            self.shown_ammo = Some(ammo);
+            ui.send_message(TextMessage::text(
+                self.ammo_text,
+                MessageDirection::ToWidget,
+                format!("Ammo: {}/{}  Grenades: {}", ammo.0, ammo.1, ammo.2),
+            ));
+        }
+    }
+    // ANCHOR_END: hud_stats
+
+    // ANCHOR: hud_interaction
+    fn update_interaction_prompt(&mut self, ui: &mut UserInterface, player: &Player) {
+        let prompt = player.interaction_prompt();
+        if self.shown_prompt.as_deref() == prompt {
+            return;
+        }
+        self.shown_prompt = prompt.map(|prompt| prompt.to_string());
+
+        ui.send_message(WidgetMessage::visibility(
+            self.interaction_text,
+            MessageDirection::ToWidget,
+            prompt.is_some(),
+        ));
+        if let Some(prompt) = prompt {
+            ui.send_message(TextMessage::text(
+                self.interaction_text,
+                MessageDirection::ToWidget,
+                prompt.to_string(),
+            ));
+        }
+    }
+    // ANCHOR_END: hud_interaction
+
+    // ANCHOR: hud_wave
+    pub fn update_wave(&mut self, ui: &mut UserInterface, wave: u32) {
+        if self.shown_wave == Some(wave) {
+            return;
+        }
+        self.shown_wave = Some(wave);
+
+        ui.send_message(WidgetMessage::visibility(
+            self.wave_text,
+            MessageDirection::ToWidget,
+            wave > 0,
+        ));
+        ui.send_message(TextMessage::text(
+            self.wave_text,
+            MessageDirection::ToWidget,
+            format!("Wave {wave}"),
+        ));
+    }
+    // ANCHOR_END: hud_wave
+
+    // ANCHOR: hud_score
+    pub fn update_score(&mut self, ui: &mut UserInterface, score: u32, high_score: u32) {
+        if self.shown_score == Some((score, high_score)) {
+            return;
+        }
+        self.shown_score = Some((score, high_score));
+
+        ui.send_message(TextMessage::text(
+            self.score_text,
+            MessageDirection::ToWidget,
+            format!("Score: {score}\nBest: {high_score}"),
+        ));
+    }
+    // ANCHOR_END: hud_score
+
+    // ANCHOR: hud_items
+    fn update_items(&mut self, ui: &mut UserInterface, player: &Player) {
+        if self.shown_items == player.items() {
+            return;
+        }
+        self.shown_items = player.items().to_vec();
+
+        let text = self
+            .shown_items
+            .iter()
+            .map(|item| item.name())
+            .collect::<Vec<_>>()
+            .join("\n");
+        ui.send_message(TextMessage::text(
+            self.items_text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+    // ANCHOR_END: hud_items
+
+    // ANCHOR: hud_water_tint
+    fn update_water_tint(&mut self, ui: &mut UserInterface, player: &Player) {
+        let swimming = player.is_swimming();
+        if self.shown_water_tint != swimming {
+            self.shown_water_tint = swimming;
+            ui.send_message(WidgetMessage::visibility(
+                self.water_tint,
+                MessageDirection::ToWidget,
+                swimming,
+            ));
+        }
+    }
+    // ANCHOR_END: hud_water_tint
+
+    // ANCHOR: hud_damage_indicators
+    fn update_damage_indicators(&mut self, ui: &mut UserInterface, player: &Player) {
+        let center = self.screen_size.scale(0.5);
+        let mut indicators = player.damage_indicators();
+        for handle in self.damage_indicators.iter() {
+            let Some((angle, opacity)) = indicators.next() else {
+                ui.send_message(WidgetMessage::visibility(
+                    *handle,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+                continue;
+            };
+
+            let position = center - Vector2::repeat(DAMAGE_INDICATOR_RADIUS);
+
+            ui.send_message(WidgetMessage::visibility(
+                *handle,
+                MessageDirection::ToWidget,
+                true,
+            ));
+            ui.send_message(WidgetMessage::desired_position(
+                *handle,
+                MessageDirection::ToWidget,
+                position,
+            ));
+            ui.send_message(VectorImageMessage::primitives(
+                *handle,
+                MessageDirection::ToWidget,
+                damage_indicator_arc(angle),
+            ));
+            ui.send_message(WidgetMessage::foreground(
+                *handle,
+                MessageDirection::ToWidget,
+                Brush::Solid(Color::from_rgba(255, 0, 0, (opacity * 255.0) as u8)),
+            ));
+        }
+    }
+    // ANCHOR_END: hud_damage_indicators
+
+    // ANCHOR: hud_hit_marker
+    fn update_hit_marker(&mut self, ui: &mut UserInterface, player: &Player) {
+        let hit_marker = player.hit_marker();
+        ui.send_message(WidgetMessage::visibility(
+            self.hit_marker,
+            MessageDirection::ToWidget,
+            hit_marker.is_some(),
+        ));
+        if let Some((opacity, killed)) = hit_marker {
+            // Kills are marked brighter than the usual hits.
+            let color = if killed {
+                Color::from_rgba(255, 60, 60, (opacity * 255.0) as u8)
+            } else {
+                Color::from_rgba(255, 255, 255, (opacity * 160.0) as u8)
+            };
+            ui.send_message(WidgetMessage::foreground(
+                self.hit_marker,
+                MessageDirection::ToWidget,
+                Brush::Solid(color),
+            ));
+        }
+    }
+
+    fn update_damage_numbers(
+        &mut self,
+        ui: &mut UserInterface,
+        player: &Player,
+        camera: Option<&Camera>,
+    ) {
+        // Numbers behind the camera cannot be projected, they are hidden as well as the unused
+        // ones.
+        let screen_size = self.screen_size;
+        let mut damage_numbers =
+            player
+                .damage_numbers()
+                .filter_map(|(position, amount, progress)| {
+                    camera
+                        .and_then(|camera| camera.project(position, screen_size))
+                        .map(|screen_position| (screen_position, amount, progress))
+                });
+        for handle in self.damage_numbers.iter() {
+            let Some((screen_position, amount, progress)) = damage_numbers.next() else {
+                ui.send_message(WidgetMessage::visibility(
+                    *handle,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+                continue;
+            };
+
+            ui.send_message(WidgetMessage::visibility(
+                *handle,
+                MessageDirection::ToWidget,
+                true,
+            ));
+            ui.send_message(WidgetMessage::desired_position(
+                *handle,
+                MessageDirection::ToWidget,
+                screen_position - Vector2::new(0.0, DAMAGE_NUMBER_RISE * progress),
+            ));
+            ui.send_message(TextMessage::text(
+                *handle,
+                MessageDirection::ToWidget,
+                format!("{}", amount.round() as u32),
+            ));
+            ui.send_message(WidgetMessage::foreground(
+                *handle,
+                MessageDirection::ToWidget,
+                Brush::Solid(Color::from_rgba(
+                    255,
+                    220,
+                    80,
+                    ((1.0 - progress) * 255.0) as u8,
+                )),
+            ));
+        }
+    }
+    // ANCHOR_END: hud_hit_marker
+
+    // ANCHOR: hud_health_bars
+    // Shows bars at the given world positions, each bar is filled according to its fraction.
+    pub fn update_health_bars(
+        &mut self,
+        ui: &mut UserInterface,
+        camera: Option<&Camera>,
+        health_bars: &[(Vector3<f32>, f32)],
+    ) {
+        let screen_size = self.screen_size;
+        let mut health_bars = health_bars.iter().filter_map(|(position, fraction)| {
+            camera
+                .and_then(|camera| camera.project(*position, screen_size))
+                .map(|screen_position| (screen_position, *fraction))
+        });
+        for (background, fill) in self.health_bars.iter() {
+            let Some((screen_position, fraction)) = health_bars.next() else {
+                ui.send_message(WidgetMessage::visibility(
+                    *background,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+                continue;
+            };
+
+            ui.send_message(WidgetMessage::visibility(
+                *background,
+                MessageDirection::ToWidget,
+                true,
+            ));
+            // The bar is centered above the head.
+            ui.send_message(WidgetMessage::desired_position(
+                *background,
+                MessageDirection::ToWidget,
+                screen_position - Vector2::new(HEALTH_BAR_WIDTH * 0.5, HEALTH_BAR_HEIGHT * 0.5),
+            ));
+            ui.send_message(WidgetMessage::width(
+                *fill,
+                MessageDirection::ToWidget,
+                HEALTH_BAR_WIDTH * fraction.clamp(0.0, 1.0),
+            ));
+        }
+    }
+    // ANCHOR_END: hud_health_bars
+
+    // ANCHOR: hud_minimap
+    // Shows the enemies around the player on a top-down map, the ones that are too far are placed
+    // at the edge.
+    pub fn update_minimap(
+        &mut self,
+        ui: &mut UserInterface,
+        player: &Player,
+        player_position: Vector3<f32>,
+        enemies: &[Vector3<f32>],
+    ) {
+        let yaw = player.yaw().to_radians();
+        let rotate = player.config.minimap_rotation;
+        let half_size = MINIMAP_SIZE * 0.5;
+        let scale = half_size / player.config.minimap_range.max(f32::EPSILON);
+
+        // Converts an offset in the XZ plane to an offset from the center of the map. The X axis
+        // points to the left of the player, and the screen Y axis points down.
+        let to_map = |offset: Vector2<f32>| {
+            let map_offset = if rotate {
+                let forward = offset.x * yaw.sin() + offset.y * yaw.cos();
+                let right = -offset.x * yaw.cos() + offset.y * yaw.sin();
+                Vector2::new(right, -forward)
+            } else {
+                Vector2::new(-offset.x, -offset.y)
+            };
+            map_offset.scale(scale)
+        };
+        let place = |ui: &mut UserInterface, marker: Handle<UiNode>, offset: Vector2<f32>| {
+            ui.send_message(WidgetMessage::desired_position(
+                marker,
+                MessageDirection::ToWidget,
+                Vector2::repeat(half_size - MINIMAP_MARKER_SIZE * 0.5) + offset,
+            ));
+        };
+
+        let heading = to_map(Vector2::new(yaw.sin(), yaw.cos()));
+        let heading_offset = heading
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_default()
+            .scale(MINIMAP_MARKER_SIZE * 1.5);
+        place(ui, self.minimap_heading, heading_offset);
+
+        let edge = half_size - MINIMAP_MARKER_SIZE * 0.5;
+        let mut enemies = enemies.iter();
+        for blip in self.minimap_blips.iter() {
+            let Some(enemy) = enemies.next() else {
+                ui.send_message(WidgetMessage::visibility(
+                    *blip,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+                continue;
+            };
+
+            let mut offset = to_map((enemy - player_position).xz());
+            let distance = offset.x.abs().max(offset.y.abs());
+            if distance > edge {
+                offset = offset.scale(edge / distance);
+            }
+            ui.send_message(WidgetMessage::visibility(
+                *blip,
+                MessageDirection::ToWidget,
+                true,
+            ));
+            place(ui, *blip, offset);
+        }
+    }
+    // ANCHOR_END: hud_minimap
+
+    // ANCHOR: hud_compass
+    // Shows the directions and the objectives on a strip, the view direction is in its center.
+    // Objectives that are out of the strip are placed at its edge, so the player knows where to
+    // turn.
+    pub fn update_compass(
+        &mut self,
+        ui: &mut UserInterface,
+        player: &Player,
+        player_position: Vector3<f32>,
+        objectives: &[Vector3<f32>],
+    ) {
+        let yaw = player.yaw();
+        let half_width = COMPASS_WIDTH * 0.5;
+        // Positive angles are to the left, so they're subtracted.
+        let to_compass = |bearing: f32| {
+            let relative = (bearing - yaw + 180.0).rem_euclid(360.0) - 180.0;
+            half_width - relative * COMPASS_WIDTH / COMPASS_RANGE
+        };
+
+        for (label, (_, bearing)) in self.compass_labels.iter().zip(COMPASS_DIRECTIONS) {
+            let x = to_compass(bearing);
+            let visible = (0.0..=COMPASS_WIDTH).contains(&x);
+            ui.send_message(WidgetMessage::visibility(
+                *label,
+                MessageDirection::ToWidget,
+                visible,
+            ));
+            ui.send_message(WidgetMessage::desired_position(
+                *label,
+                MessageDirection::ToWidget,
+                Vector2::new(x - COMPASS_LABEL_WIDTH * 0.5, 0.0),
+            ));
+        }
+
+        let mut objectives = objectives.iter();
+        for marker in self.compass_markers.iter() {
+            let Some(objective) = objectives.next() else {
+                ui.send_message(WidgetMessage::visibility(
+                    *marker,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+                continue;
+            };
+
+            let offset = objective - player_position;
+            let bearing = offset.x.atan2(offset.z).to_degrees();
+            let x = to_compass(bearing).clamp(0.0, COMPASS_WIDTH);
+            ui.send_message(WidgetMessage::visibility(
+                *marker,
+                MessageDirection::ToWidget,
+                true,
+            ));
+            ui.send_message(WidgetMessage::desired_position(
+                *marker,
+                MessageDirection::ToWidget,
+                Vector2::new(
+                    (x - COMPASS_MARKER_SIZE * 0.5).clamp(0.0, COMPASS_WIDTH - COMPASS_MARKER_SIZE),
+                    COMPASS_HEIGHT - COMPASS_MARKER_SIZE,
+                ),
+            ));
+        }
+    }
+    // ANCHOR_END: hud_compass
+
+    // ANCHOR: hud_objective_markers
+    // Shows the objectives at their positions in the world with the distance to them. Objectives
+    // that are off the screen are placed at its edge in their direction.
+    pub fn update_objective_markers(
+        &mut self,
+        ui: &mut UserInterface,
+        camera: Option<&Camera>,
+        player_position: Vector3<f32>,
+        objective_markers: &ObjectiveMarkers,
+    ) {
+        let screen_size = self.screen_size;
+        let mut objectives = objective_markers.iter();
+        for (marker, text) in self.objective_markers.iter() {
+            let (Some(camera), Some(objective)) = (camera, objectives.next()) else {
+                ui.send_message(WidgetMessage::visibility(
+                    *marker,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+                continue;
+            };
+
+            let screen_position = project_to_screen_edge(
+                camera,
+                objective.position,
+                screen_size,
+                OBJECTIVE_MARKER_MARGIN,
+            );
+            let distance = objective.position.metric_distance(&player_position);
+            ui.send_message(WidgetMessage::visibility(
+                *marker,
+                MessageDirection::ToWidget,
+                true,
+            ));
+            ui.send_message(WidgetMessage::desired_position(
+                *marker,
+                MessageDirection::ToWidget,
+                screen_position - Vector2::repeat(OBJECTIVE_MARKER_SIZE * 0.5),
+            ));
+            ui.send_message(TextMessage::text(
+                *text,
+                MessageDirection::ToWidget,
+                format!("{}\n{} m", objective.label, distance.round() as u32),
+            ));
+        }
+    }
+    // ANCHOR_END: hud_objective_markers
+}
+
+// ANCHOR: damage_indicator_arc
+// The angle is in degrees, zero is at the top of the screen and positive angles go clockwise. The
+// arc is centered at the given angle, in the local coordinates of an indicator, where the circle
+// center is at (radius, radius).
+fn damage_indicator_arc(angle: f32) -> Vec<Primitive> {
+    let radius = DAMAGE_INDICATOR_RADIUS - DAMAGE_INDICATOR_THICKNESS;
+    let point = |angle: f32| {
+        let angle = angle.to_radians();
+        Vector2::repeat(DAMAGE_INDICATOR_RADIUS)
+            + Vector2::new(angle.sin(), -angle.cos()).scale(radius)
+    };
+    let start = angle - DAMAGE_INDICATOR_ARC * 0.5;
+    let step = DAMAGE_INDICATOR_ARC / DAMAGE_INDICATOR_SEGMENTS as f32;
+    (0..DAMAGE_INDICATOR_SEGMENTS)
+        .map(|i| Primitive::Line {
+            begin: point(start + step * i as f32),
+            end: point(start + step * (i + 1) as f32),
+            thickness: DAMAGE_INDICATOR_THICKNESS,
+        })
+        .collect()
+}
+// ANCHOR_END: damage_indicator_arc
+
+// ANCHOR: project_to_screen_edge
+// Projects a world position to the screen, positions that are off the screen are moved to its edge
+// along the direction from the center of the screen, so they keep the margin from the edge.
+// Positions behind the camera are mirrored, otherwise they'd be shown on the opposite side.
+fn project_to_screen_edge(
+    camera: &Camera,
+    position: Vector3<f32>,
+    screen_size: Vector2<f32>,
+    margin: f32,
+) -> Vector2<f32> {
+    let center = screen_size.scale(0.5);
+    let offset = position - camera.global_position();
+    let in_front = offset.dot(&camera.look_vector()) > 0.0;
+
+    let direction = match camera.project(position, screen_size).filter(|_| in_front) {
+        Some(screen_position) => {
+            if (margin..=screen_size.x - margin).contains(&screen_position.x)
+                && (margin..=screen_size.y - margin).contains(&screen_position.y)
+            {
+                return screen_position;
+            }
+            screen_position - center
+        }
+        // The screen X axis is opposite to the side vector of the camera, and the Y axis points
+        // down. A position right behind the camera is put at the bottom.
+        None => Vector2::new(
+            -offset.dot(&camera.side_vector()),
+            -offset.dot(&camera.up_vector()),
+        )
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(|| Vector2::new(0.0, 1.0)),
+    };
+
+    // The direction is scaled until it touches the closest edge.
+    let half_size = (center - Vector2::repeat(margin)).sup(&Vector2::default());
+    let scale = (half_size.x / direction.x.abs().max(f32::EPSILON))
+        .min(half_size.y / direction.y.abs().max(f32::EPSILON));
+    center + direction.scale(scale)
+}
+// ANCHOR_END: project_to_screen_edge
@@ -0,0 +1,65 @@
+use fyrox::{
+    core::{pool::Handle, reflect::prelude::*, visitor::prelude::*},
+    scene::{graph::Graph, node::Node},
+    script::{ScriptMessageContext, ScriptMessagePayload},
+};
+
+// ANCHOR: interaction_component
+// Add this component to a script to make the object interactable. The script must be
+// registered as a component provider by using `#[component(include)]` attribute on the field.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct Interaction {
+    // A text that is shown on the HUD when the player looks at the object.
+    pub prompt: String,
+}
+
+impl Default for Interaction {
+    fn default() -> Self {
+        Self {
+            prompt: "Press E".to_string(),
+        }
+    }
+}
+// ANCHOR_END: interaction_component
+
+// ANCHOR: interact_message
+#[derive(Debug)]
+pub struct InteractMessage {
+    // A handle of the player that interacts with the object.
+    pub player: Handle<Node>,
+}
+// ANCHOR_END: interact_message
+
+// ANCHOR: interactable
+// Implement this trait for a script to react to interactions. Such script must subscribe to
+// `InteractMessage` in its `on_start` method and call `handle_interact_message` in `on_message`.
+pub trait Interactable {
+    fn interact(&mut self, player: Handle<Node>, ctx: &mut ScriptMessageContext);
+}
+
+pub fn handle_interact_message<T: Interactable>(
+    interactable: &mut T,
+    message: &mut dyn ScriptMessagePayload,
+    ctx: &mut ScriptMessageContext,
+) {
+    if let Some(interact) = message.downcast_ref::<InteractMessage>() {
+        interactable.interact(interact.player, ctx);
+    }
+}
+// ANCHOR_END: interactable
+
+// ANCHOR: find_interaction
+// Interactable script could be assigned either to a collider or to its rigid body.
+pub fn find_interaction(
+    graph: &Graph,
+    collider: Handle<Node>,
+) -> Option<(Handle<Node>, &Interaction)> {
+    let parent = graph.try_get(collider)?.parent();
+    [collider, parent].into_iter().find_map(|handle| {
+        graph
+            .try_get(handle)
+            .and_then(|node| node.try_get_script_component::<Interaction>())
+            .map(|interaction| (handle, interaction))
+    })
+}
+// ANCHOR_END: find_interaction
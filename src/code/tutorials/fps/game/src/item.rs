@@ -0,0 +1,24 @@
+use fyrox::core::{reflect::prelude::*, visitor::prelude::*};
+
+// ANCHOR: item_id
+// Things that the player can carry. Add a new variant here to add a new item, the rest of the
+// code (pickups, doors, HUD) works with any of them.
+#[derive(Visit, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemId {
+    #[default]
+    RedKey,
+    BlueKey,
+    YellowKey,
+}
+
+impl ItemId {
+    // A name that is shown on the HUD.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ItemId::RedKey => "Red Key",
+            ItemId::BlueKey => "Blue Key",
+            ItemId::YellowKey => "Yellow Key",
+        }
+    }
+}
+// ANCHOR_END: item_id
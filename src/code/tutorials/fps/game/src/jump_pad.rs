@@ -0,0 +1,77 @@
+use crate::{
+    sound::play_sound,
+    trigger::{update_trigger, with_player, TriggerHandler, TriggerVolume},
+};
+use fyrox::{
+    core::{
+        algebra::Vector3, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    scene::{node::Node, sound::SoundBufferResource},
+    script::{ScriptContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "4e36f21b-fc6f-4322-85bf-d2edafee6866")]
+#[visit(optional)]
+pub struct JumpPad {
+    // ANCHOR: jump_pad_fields
+    // Speed (in m/s) with which the player is thrown up.
+    launch_speed: InheritableVariable<f32>,
+
+    // If set, the player is thrown along the up vector of the pad instead of straight up, this
+    // way a tilted pad works as a launcher.
+    directional: InheritableVariable<bool>,
+
+    launch_sound: InheritableVariable<Option<SoundBufferResource>>,
+
+    #[reflect(hidden)]
+    trigger: TriggerVolume,
+    // ANCHOR_END: jump_pad_fields
+}
+
+impl Default for JumpPad {
+    fn default() -> Self {
+        Self {
+            launch_speed: 15.0.into(),
+            directional: false.into(),
+            launch_sound: Default::default(),
+            trigger: Default::default(),
+        }
+    }
+}
+
+// ANCHOR: jump_pad_enter
+impl TriggerHandler for JumpPad {
+    fn trigger_volume(&mut self) -> &mut TriggerVolume {
+        &mut self.trigger
+    }
+
+    fn on_enter(&mut self, player: Handle<Node>, ctx: &mut ScriptContext) {
+        let pad = &ctx.scene.graph[ctx.handle];
+        let position = pad.global_position();
+        let direction = if *self.directional {
+            pad.up_vector()
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_default()
+        } else {
+            Vector3::y()
+        };
+        let velocity = direction.scale(*self.launch_speed);
+
+        with_player(&mut ctx.scene.graph, player, |player| {
+            player.launch(velocity, true)
+        });
+
+        if let Some(sound) = self.launch_sound.as_ref() {
+            play_sound(&mut ctx.scene.graph, sound, position, 1.0);
+        }
+    }
+}
+// ANCHOR_END: jump_pad_enter
+
+impl ScriptTrait for JumpPad {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        update_trigger(self, ctx);
+    }
+}
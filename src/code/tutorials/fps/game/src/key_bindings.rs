@@ -0,0 +1,65 @@
+use fyrox::{
+    core::{reflect::prelude::*, visitor::prelude::*},
+    gui::message::KeyCode,
+};
+
+// ANCHOR: key_bindings
+// Keys that are used to control the player. UI key codes are used here, because unlike the
+// key codes of the OS events they could be serialized and edited in the editor. Use
+// `fyrox::utils::translate_key_to_ui` to convert the latter to the former.
+#[derive(Visit, Reflect, Debug, Clone)]
+#[visit(optional)]
+pub struct KeyBindings {
+    pub forward: KeyCode,
+    pub backward: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub jump: KeyCode,
+    pub sprint: KeyCode,
+    pub crouch: KeyCode,
+    pub reload: KeyCode,
+    pub interact: KeyCode,
+    pub switch_fire_mode: KeyCode,
+    pub pause: KeyCode,
+    pub noclip: KeyCode,
+    pub dash: KeyCode,
+    pub grapple: KeyCode,
+    pub melee: KeyCode,
+    pub flashlight: KeyCode,
+    pub camera_mode: KeyCode,
+    // Holding the key cooks a grenade, releasing it throws the grenade.
+    pub grenade: KeyCode,
+    // Toggles the spectator mode, the other two switch between the targets that are followed.
+    pub spectate: KeyCode,
+    pub spectate_next: KeyCode,
+    pub spectate_previous: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            forward: KeyCode::KeyW,
+            backward: KeyCode::KeyS,
+            left: KeyCode::KeyA,
+            right: KeyCode::KeyD,
+            jump: KeyCode::Space,
+            sprint: KeyCode::ShiftLeft,
+            crouch: KeyCode::ControlLeft,
+            reload: KeyCode::KeyR,
+            interact: KeyCode::KeyE,
+            switch_fire_mode: KeyCode::KeyB,
+            pause: KeyCode::Escape,
+            noclip: KeyCode::KeyV,
+            dash: KeyCode::KeyQ,
+            grapple: KeyCode::KeyG,
+            melee: KeyCode::KeyF,
+            flashlight: KeyCode::KeyL,
+            camera_mode: KeyCode::KeyT,
+            grenade: KeyCode::KeyX,
+            spectate: KeyCode::KeyP,
+            spectate_next: KeyCode::BracketRight,
+            spectate_previous: KeyCode::BracketLeft,
+        }
+    }
+}
+// ANCHOR_END: key_bindings
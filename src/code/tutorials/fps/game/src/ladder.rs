@@ -0,0 +1,44 @@
+use crate::trigger::{update_trigger, with_player, TriggerHandler, TriggerVolume};
+use fyrox::{
+    core::{pool::Handle, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    scene::node::Node,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "c30d9419-7aad-422b-9840-54c98b177431")]
+#[visit(optional)]
+pub struct Ladder {
+    // ANCHOR: ladder_fields
+    #[reflect(hidden)]
+    trigger: TriggerVolume,
+    // ANCHOR_END: ladder_fields
+}
+
+// ANCHOR: ladder_update
+impl TriggerHandler for Ladder {
+    fn trigger_volume(&mut self) -> &mut TriggerVolume {
+        &mut self.trigger
+    }
+
+    fn on_enter(&mut self, player: Handle<Node>, ctx: &mut ScriptContext) {
+        with_player(&mut ctx.scene.graph, player, |player| player.enter_ladder());
+    }
+
+    fn on_exit(&mut self, player: Handle<Node>, ctx: &mut ScriptContext) {
+        with_player(&mut ctx.scene.graph, player, |player| player.exit_ladder());
+    }
+}
+// ANCHOR_END: ladder_update
+
+impl ScriptTrait for Ladder {
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        with_player(&mut ctx.scene.graph, self.trigger.player(), |player| {
+            player.exit_ladder()
+        });
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        update_trigger(self, ctx);
+    }
+}
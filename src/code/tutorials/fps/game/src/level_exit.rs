@@ -0,0 +1,60 @@
+use crate::{
+    player::Player,
+    trigger::{update_trigger, TriggerHandler, TriggerVolume},
+    Game,
+};
+use fyrox::{
+    core::{
+        pool::Handle, reflect::prelude::*, type_traits::prelude::*, variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::node::Node,
+    script::{ScriptContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "44371d50-f55b-4695-8460-57175342af34")]
+#[visit(optional)]
+pub struct LevelExit {
+    // ANCHOR: level_exit_fields
+    // A path to the scene that will be loaded when the player enters the trigger.
+    next_level: InheritableVariable<String>,
+
+    // The exit works only once, otherwise the loading could be requested several times.
+    #[reflect(hidden)]
+    activated: bool,
+
+    #[reflect(hidden)]
+    trigger: TriggerVolume,
+    // ANCHOR_END: level_exit_fields
+}
+
+impl TriggerHandler for LevelExit {
+    fn trigger_volume(&mut self) -> &mut TriggerVolume {
+        &mut self.trigger
+    }
+
+    fn on_enter(&mut self, body: Handle<Node>, ctx: &mut ScriptContext) {
+        // ANCHOR: level_exit_activation
+        let graph = &ctx.scene.graph;
+        let Some(player) = graph[body].try_get_script::<Player>() else {
+            return;
+        };
+
+        // Health, weapons and ammo go to the next level with the player.
+        let state = player.save_state(graph, body);
+        ctx.plugins
+            .get_mut::<Game>()
+            .change_level(self.next_level.clone_inner(), state);
+        self.activated = true;
+        // ANCHOR_END: level_exit_activation
+    }
+}
+
+impl ScriptTrait for LevelExit {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if !self.activated && !self.next_level.is_empty() {
+            update_trigger(self, ctx);
+        }
+    }
+}
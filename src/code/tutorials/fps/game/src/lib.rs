@@ -10,8 +10,78 @@ use std::path::Path;
 // Add this line
 pub mod player;
 // ANCHOR_END: player_mod_reg
+use crate::{
+    camera_track::CameraTrack,
+    checkpoint::Checkpoint,
+    door::Door,
+    enemy::Enemy,
+    explosion::ExplosionFlash,
+    gamepad::GamepadInput,
+    grenade::Grenade,
+    hazard::Hazard,
+    health::Health,
+    hud::Hud,
+    jump_pad::JumpPad,
+    ladder::Ladder,
+    level_exit::LevelExit,
+    loading_screen::LoadingScreen,
+    main_menu::{MainMenu, MainMenuAction},
+    objective::{Objective, ObjectiveMarkers},
+    pickup::Pickup,
+    player_state::PlayerState,
+    rocket::Rocket,
+    score::HighScore,
+    settings::Settings,
+    settings_menu::SettingsMenu,
+    spawner::Spawner,
+    surface::SurfaceType,
+    teleporter::Teleporter,
+    water::WaterVolume,
+};
+use fyrox::{
+    core::{log::Log, visitor::VisitError},
+    event::{ElementState, Event, WindowEvent},
+    gui::message::UiMessage,
+    keyboard::{KeyCode, PhysicalKey},
+    scene::{camera::Camera, node::Node},
+};
+
 pub mod bot;
+pub mod camera_track;
+pub mod checkpoint;
+pub mod door;
+pub mod effect;
+pub mod enemy;
+pub mod explosion;
+pub mod gamepad;
+pub mod grenade;
+pub mod hazard;
+pub mod health;
+pub mod hud;
+pub mod interaction;
+pub mod item;
+pub mod jump_pad;
+pub mod key_bindings;
+pub mod ladder;
+pub mod level_exit;
+pub mod loading_screen;
+pub mod main_menu;
+pub mod objective;
+pub mod pickup;
+pub mod player_config;
+pub mod player_state;
 pub mod projectile;
+pub mod rocket;
+pub mod score;
+pub mod settings;
+pub mod settings_menu;
+pub mod sound;
+pub mod spawner;
+pub mod storage;
+pub mod surface;
+pub mod teleporter;
+pub mod trigger;
+pub mod water;
 pub mod weapon;
 
 pub struct GameConstructor;
@@ -45,6 +115,132 @@ impl PluginConstructor for GameConstructor {
             .script_constructors
             .add::<Bot>("Bot");
         // ANCHOR_END: bot_script_reg
+
+        // ANCHOR: health_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Health>("Health");
+        // ANCHOR_END: health_script_reg
+
+        // ANCHOR: rocket_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Rocket>("Rocket");
+        // ANCHOR_END: rocket_script_reg
+
+        // ANCHOR: pickup_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Pickup>("Pickup");
+        // ANCHOR_END: pickup_script_reg
+
+        // ANCHOR: enemy_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Enemy>("Enemy");
+        // ANCHOR_END: enemy_script_reg
+
+        // ANCHOR: checkpoint_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Checkpoint>("Checkpoint");
+        // ANCHOR_END: checkpoint_script_reg
+
+        // ANCHOR: water_volume_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<WaterVolume>("WaterVolume");
+        // ANCHOR_END: water_volume_script_reg
+
+        // ANCHOR: ladder_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Ladder>("Ladder");
+        // ANCHOR_END: ladder_script_reg
+
+        // ANCHOR: surface_type_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<SurfaceType>("SurfaceType");
+        // ANCHOR_END: surface_type_script_reg
+
+        // ANCHOR: spawner_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Spawner>("Spawner");
+        // ANCHOR_END: spawner_script_reg
+
+        // ANCHOR: level_exit_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<LevelExit>("LevelExit");
+        // ANCHOR_END: level_exit_script_reg
+
+        // ANCHOR: door_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Door>("Door");
+        // ANCHOR_END: door_script_reg
+
+        // ANCHOR: hazard_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Hazard>("Hazard");
+        // ANCHOR_END: hazard_script_reg
+
+        // ANCHOR: jump_pad_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<JumpPad>("JumpPad");
+        // ANCHOR_END: jump_pad_script_reg
+
+        // ANCHOR: teleporter_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Teleporter>("Teleporter");
+        // ANCHOR_END: teleporter_script_reg
+
+        // ANCHOR: camera_track_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<CameraTrack>("CameraTrack");
+        // ANCHOR_END: camera_track_script_reg
+
+        // ANCHOR: explosion_flash_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<ExplosionFlash>("ExplosionFlash");
+        // ANCHOR_END: explosion_flash_script_reg
+
+        // ANCHOR: grenade_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Grenade>("Grenade");
+        // ANCHOR_END: grenade_script_reg
+
+        // ANCHOR: objective_script_reg
+        context
+            .serialization_context
+            .script_constructors
+            .add::<Objective>("Objective");
+        // ANCHOR_END: objective_script_reg
     }
 
     fn create_instance(&self, scene_path: Option<&str>, context: PluginContext) -> Box<dyn Plugin> {
@@ -52,26 +248,371 @@ impl PluginConstructor for GameConstructor {
     }
 }
 
+// ANCHOR: level_transition
+// Time (in seconds) of the fade to black before the loading of the next level and of the fade
+// back after it.
+const FADE_DURATION: f32 = 0.5;
+
+enum LevelTransition {
+    FadeOut { path: String, time: f32 },
+    FadeIn { time: f32 },
+}
+// ANCHOR_END: level_transition
+
 pub struct Game {
     scene: Handle<Scene>,
+    pub gamepad: GamepadInput,
+    hud: Hud,
+    main_menu: MainMenu,
+    loading_screen: LoadingScreen,
+    transition: Option<LevelTransition>,
+    // The state of the player that is moved to the next level, the player takes it on start.
+    pub carried_state: Option<PlayerState>,
+    // Scripts must not update the game state while it is set.
+    pub paused: bool,
+    // Number of the current wave of enemies, it is set by the spawner.
+    pub wave: u32,
+    // Points that the player got for killing enemies.
+    pub score: u32,
+    // Speed of the world simulation, where 1.0 is the normal speed. Scripts multiply their time
+    // step by it.
+    pub time_scale: f32,
+    // Markers that are shown on the compass and over the world, scripts add and remove them.
+    pub objective_markers: ObjectiveMarkers,
+    high_score: HighScore,
+    settings: Settings,
+    settings_menu: SettingsMenu,
+    // Settings must be applied to the scene when they're changed or a new scene is loaded.
+    settings_changed: bool,
 }
 
+const GAME_SCENE_PATH: &str = "data/scene.rgs";
+const HIGH_SCORE_PATH: &str = "high_score.bin";
+const SETTINGS_PATH: &str = "settings.bin";
+
 impl Game {
-    pub fn new(scene_path: Option<&str>, context: PluginContext) -> Self {
-        context
-            .async_scene_loader
-            .request(scene_path.unwrap_or("data/scene.rgs"));
+    pub fn new(scene_path: Option<&str>, mut context: PluginContext) -> Self {
+        // Default settings are used on the first run.
+        let settings = Settings::load(Path::new(SETTINGS_PATH)).unwrap_or_default();
 
-        Self {
+        let mut game = Self {
             scene: Handle::NONE,
+            gamepad: GamepadInput::new(),
+            hud: Hud::new(context.user_interface),
+            main_menu: MainMenu::new(context.user_interface),
+            loading_screen: LoadingScreen::new(context.user_interface),
+            transition: None,
+            carried_state: None,
+            paused: false,
+            wave: 0,
+            score: 0,
+            time_scale: 1.0,
+            objective_markers: Default::default(),
+            // There's no file until the first run is finished.
+            high_score: HighScore::load(Path::new(HIGH_SCORE_PATH)).unwrap_or_default(),
+            settings_menu: SettingsMenu::new(context.user_interface, &settings),
+            settings,
+            settings_changed: true,
+        };
+
+        // ANCHOR: main_menu_start
+        // The editor runs the game with the scene that is being edited, the menu is skipped then.
+        if let Some(scene_path) = scene_path {
+            game.load_level(&mut context, scene_path);
+        }
+        // ANCHOR_END: main_menu_start
+
+        game
+    }
+
+    // ANCHOR: load_level
+    // Starts loading of a level in background, the current one is removed when the new one is
+    // ready. The loading screen is shown in the meantime.
+    pub fn load_level(&mut self, context: &mut PluginContext, path: &str) {
+        context.async_scene_loader.request(path);
+        self.paused = false;
+        self.main_menu.set_visible(context.user_interface, false);
+        self.main_menu.set_error(context.user_interface, None);
+        self.loading_screen
+            .set_visible(context.user_interface, true);
+    }
+    // ANCHOR_END: load_level
+
+    // ANCHOR: change_level
+    // Fades the screen out and loads the next level, the player keeps the given state there.
+    pub fn change_level(&mut self, path: String, state: PlayerState) {
+        if self.transition.is_some() {
+            return;
+        }
+        self.carried_state = Some(state);
+        self.transition = Some(LevelTransition::FadeOut { path, time: 0.0 });
+    }
+
+    fn update_transition(&mut self, context: &mut PluginContext) {
+        let ui = &mut *context.user_interface;
+        match self.transition.as_mut() {
+            Some(LevelTransition::FadeOut { path, time }) => {
+                *time += context.dt;
+                self.loading_screen.set_fade(ui, *time / FADE_DURATION);
+                if *time >= FADE_DURATION {
+                    let path = std::mem::take(path);
+                    self.transition = None;
+                    self.load_level(context, &path);
+                }
+            }
+            Some(LevelTransition::FadeIn { time }) => {
+                *time += context.dt;
+                self.loading_screen
+                    .set_fade(ui, 1.0 - *time / FADE_DURATION);
+                if *time >= FADE_DURATION {
+                    self.transition = None;
+                }
+            }
+            None => (),
+        }
+    }
+    // ANCHOR_END: change_level
+}
+
+// ANCHOR: score
+impl Game {
+    pub fn add_score(&mut self, points: u32) {
+        self.score += points;
+        self.high_score.score = self.high_score.score.max(self.score);
+    }
+}
+// ANCHOR_END: score
+
+// ANCHOR: time_scale
+// Time scale of the slow motion that could be toggled for demonstration.
+const SLOW_MOTION_SCALE: f32 = 0.3;
+
+impl Game {
+    pub fn toggle_slow_motion(&mut self) {
+        self.time_scale = if self.time_scale < 1.0 {
+            1.0
+        } else {
+            SLOW_MOTION_SCALE
+        };
+    }
+
+    // The physics uses the scaled time step too, so bodies fall and fly slower as well. The pause
+    // stops the physics entirely.
+    fn update_time_scale(&mut self, context: &mut PluginContext) {
+        let time_scale = if self.paused { 0.0 } else { self.time_scale };
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            scene.graph.physics.integration_parameters.dt = Some(context.dt * time_scale);
+        }
+    }
+}
+// ANCHOR_END: time_scale
+
+// ANCHOR: apply_settings
+impl Game {
+    fn apply_settings(&mut self, context: &mut PluginContext) {
+        let Some(scene) = context.scenes.try_get_mut(self.scene) else {
+            return;
+        };
+
+        if let Some(player) = scene
+            .graph
+            .linear_iter_mut()
+            .find_map(|node| node.try_get_script_mut::<Player>())
+        {
+            self.settings.apply(&mut player.config);
+        }
+        scene
+            .graph
+            .sound_context
+            .state()
+            .bus_graph_mut()
+            .primary_bus_mut()
+            .set_gain(self.settings.volume);
+
+        self.settings_changed = false;
+    }
+}
+// ANCHOR_END: apply_settings
+
+// ANCHOR: quick_save
+const QUICK_SAVE_PATH: &str = "quick_save.bin";
+
+impl Game {
+    fn find_player(&self, context: &PluginContext) -> Option<Handle<Node>> {
+        let scene = context.scenes.try_get(self.scene)?;
+        scene
+            .graph
+            .pair_iter()
+            .find(|(_, node)| node.has_script::<Player>())
+            .map(|(handle, _)| handle)
+    }
+
+    fn quick_save(&self, context: &PluginContext) {
+        let Some(player) = self.find_player(context) else {
+            return;
+        };
+        let graph = &context.scenes[self.scene].graph;
+        if let Some(script) = graph[player].try_get_script::<Player>() {
+            let mut state = script.save_state(graph, player);
+            Log::verify(state.save(Path::new(QUICK_SAVE_PATH)));
+        }
+    }
+
+    fn quick_load(&self, context: &mut PluginContext) {
+        let Some(player) = self.find_player(context) else {
+            return;
+        };
+        match PlayerState::load(Path::new(QUICK_SAVE_PATH)) {
+            Ok(state) => {
+                let graph = &mut context.scenes[self.scene].graph;
+                if let Some(script) = graph[player].try_get_script_mut::<Player>() {
+                    script.load_state(state);
+                }
+            }
+            Err(err) => Log::err(format!("Unable to load the quick save: {err:?}")),
         }
     }
 }
+// ANCHOR_END: quick_save
 
 impl Plugin for Game {
+    fn update(&mut self, context: &mut PluginContext) {
+        // ANCHOR: clear_lines_update
+        // Tracers and the grapple rope are drawn as lines, that are kept until they're cleared.
+        // Plugins are updated before scripts, so the scripts add the lines again on each frame.
+        if let Some(scene) = context.scenes.try_get_mut(self.scene) {
+            scene.drawing_context.clear_lines();
+        }
+        // ANCHOR_END: clear_lines_update
+
+        self.gamepad.update();
+
+        self.loading_screen
+            .update(context.user_interface, context.dt);
+        self.update_transition(context);
+        self.update_time_scale(context);
+
+        // ANCHOR: settings_menu_update
+        // The menu is a part of the pause, the settings are saved when the game continues.
+        if self.settings_menu.is_visible() && !self.paused {
+            Log::verify(self.settings.save(Path::new(SETTINGS_PATH)));
+        }
+        self.settings_menu
+            .set_visible(context.user_interface, self.paused);
+        if self.settings_changed {
+            self.apply_settings(context);
+        }
+        // ANCHOR_END: settings_menu_update
+
+        // HUD shows the state of the player, so it must be synchronized with it.
+        if let Some(scene) = context.scenes.try_get(self.scene) {
+            if let Some((player, player_position)) = scene.graph.linear_iter().find_map(|node| {
+                node.try_get_script::<Player>()
+                    .map(|player| (player, node.global_position()))
+            }) {
+                // The camera is needed to show things at their world positions.
+                let camera = scene.graph.try_get_of_type::<Camera>(player.camera());
+                self.hud.update(context.user_interface, player, camera);
+
+                let health_bars = scene
+                    .graph
+                    .pair_iter()
+                    .filter_map(|(handle, node)| {
+                        node.try_get_script::<Enemy>()?
+                            .health_bar(&scene.graph, handle)
+                    })
+                    .collect::<Vec<_>>();
+                self.hud
+                    .update_health_bars(context.user_interface, camera, &health_bars);
+                self.hud.update_wave(context.user_interface, self.wave);
+
+                // ANCHOR: minimap_update
+                let enemies = scene
+                    .graph
+                    .linear_iter()
+                    .filter(|node| node.has_script::<Enemy>())
+                    .map(|node| node.global_position())
+                    .collect::<Vec<_>>();
+                self.hud
+                    .update_minimap(context.user_interface, player, player_position, &enemies);
+                // ANCHOR_END: minimap_update
+
+                // ANCHOR: compass_update
+                let objectives = self
+                    .objective_markers
+                    .iter()
+                    .map(|marker| marker.position)
+                    .collect::<Vec<_>>();
+                self.hud.update_compass(
+                    context.user_interface,
+                    player,
+                    player_position,
+                    &objectives,
+                );
+                // ANCHOR_END: compass_update
+
+                // ANCHOR: objective_markers_update
+                self.hud.update_objective_markers(
+                    context.user_interface,
+                    camera,
+                    player_position,
+                    &self.objective_markers,
+                );
+                // ANCHOR_END: objective_markers_update
+                self.hud
+                    .update_score(context.user_interface, self.score, self.high_score.score);
+            }
+        }
+    }
+
+    // ANCHOR: quick_save_keys
+    fn on_os_event(&mut self, event: &Event<()>, mut context: PluginContext) {
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event, .. },
+            ..
+        } = event
+        {
+            if event.state == ElementState::Pressed && !event.repeat {
+                match event.physical_key {
+                    PhysicalKey::Code(KeyCode::F5) => self.quick_save(&context),
+                    PhysicalKey::Code(KeyCode::F9) => self.quick_load(&mut context),
+                    PhysicalKey::Code(KeyCode::F6) => self.toggle_slow_motion(),
+                    _ => (),
+                }
+            }
+        }
+    }
+    // ANCHOR_END: quick_save_keys
+
+    // ANCHOR: settings_ui_message
+    fn on_ui_message(&mut self, context: &mut PluginContext, message: &UiMessage) {
+        // ANCHOR: main_menu_ui_message
+        match self.main_menu.handle_ui_message(message) {
+            Some(MainMenuAction::Start) => self.load_level(context, GAME_SCENE_PATH),
+            Some(MainMenuAction::Quit) => context.loop_controller.exit(),
+            None => (),
+        }
+        // ANCHOR_END: main_menu_ui_message
+
+        if self
+            .settings_menu
+            .handle_ui_message(message, &mut self.settings)
+        {
+            self.settings_changed = true;
+        }
+    }
+    // ANCHOR_END: settings_ui_message
+
+    // ANCHOR: high_score_save
+    fn on_deinit(&mut self, _context: PluginContext) {
+        Log::verify(self.high_score.save(Path::new(HIGH_SCORE_PATH)));
+    }
+    // ANCHOR_END: high_score_save
+
     fn on_scene_begin_loading(&mut self, _path: &Path, ctx: &mut PluginContext) {
         if self.scene.is_some() {
             ctx.scenes.remove(self.scene);
+            self.scene = Handle::NONE;
         }
     }
 
@@ -80,8 +621,43 @@ impl Plugin for Game {
         _path: &Path,
         scene: Handle<Scene>,
         _data: &[u8],
-        _context: &mut PluginContext,
+        context: &mut PluginContext,
     ) {
         self.scene = scene;
+        self.settings_changed = true;
+
+        // ANCHOR: main_menu_hide
+        self.loading_screen
+            .set_visible(context.user_interface, false);
+        self.hud.set_visible(context.user_interface, true);
+        // ANCHOR_END: main_menu_hide
+
+        // The screen is black after the level exit, so it fades back in.
+        if self.carried_state.is_some() {
+            self.transition = Some(LevelTransition::FadeIn { time: 0.0 });
+        }
+    }
+
+    // ANCHOR: level_loading_failed
+    fn on_scene_loading_failed(
+        &mut self,
+        path: &Path,
+        error: &VisitError,
+        context: &mut PluginContext,
+    ) {
+        let message = format!("Unable to load {}: {error:?}", path.display());
+        Log::err(&message);
+
+        // The game goes back to the menu, so the player could try again.
+        self.carried_state = None;
+        self.transition = None;
+        self.loading_screen.set_fade(context.user_interface, 0.0);
+        self.loading_screen
+            .set_visible(context.user_interface, false);
+        self.hud.set_visible(context.user_interface, false);
+        self.main_menu.set_visible(context.user_interface, true);
+        self.main_menu
+            .set_error(context.user_interface, Some(&message));
     }
+    // ANCHOR_END: level_loading_failed
 }
@@ -0,0 +1,104 @@
+use fyrox::{
+    core::{color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        message::MessageDirection,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+
+// ANCHOR: loading_screen
+// A screen that covers everything while a level is being loaded.
+pub struct LoadingScreen {
+    root: Handle<UiNode>,
+    text: Handle<UiNode>,
+    // A black overlay that hides the game during level transitions.
+    fade: Handle<UiNode>,
+    // Time since the loading has started, it is used to animate the text.
+    loading_time: Option<f32>,
+}
+
+impl LoadingScreen {
+    pub fn new(ui: &mut UserInterface) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center),
+        )
+        .with_text("Loading")
+        .build(ctx);
+
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 255)))
+                .with_child(text),
+        )
+        .build(ctx);
+
+        // It is created after the loading screen, so it is drawn on top of everything. It must
+        // not catch clicks, otherwise the menu would not work.
+        let fade = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_hit_test_visibility(false)
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 0))),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            text,
+            fade,
+            loading_time: None,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.loading_time.is_some()
+    }
+
+    pub fn set_visible(&mut self, ui: &mut UserInterface, visible: bool) {
+        self.loading_time = if visible { Some(0.0) } else { None };
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+
+    // Zero means that the game is fully visible and one - that the screen is black.
+    pub fn set_fade(&mut self, ui: &mut UserInterface, fade: f32) {
+        ui.send_message(WidgetMessage::background(
+            self.fade,
+            MessageDirection::ToWidget,
+            Brush::Solid(Color::from_rgba(
+                0,
+                0,
+                0,
+                (fade.clamp(0.0, 1.0) * 255.0) as u8,
+            )),
+        ));
+    }
+
+    pub fn update(&mut self, ui: &mut UserInterface, dt: f32) {
+        let Some(loading_time) = self.loading_time.as_mut() else {
+            return;
+        };
+        *loading_time += dt;
+
+        // The scene loader does not report the progress, so the dots just show that the game is
+        // not frozen.
+        let dots = (*loading_time * 3.0) as usize % 4;
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            format!("Loading{}", ".".repeat(dots)),
+        ));
+    }
+}
+// ANCHOR_END: loading_screen
@@ -0,0 +1,128 @@
+use fyrox::{
+    core::{color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        button::{ButtonBuilder, ButtonMessage},
+        formatted_text::WrapMode,
+        message::{MessageDirection, UiMessage},
+        stack_panel::StackPanelBuilder,
+        text::{TextBuilder, TextMessage},
+        widget::{WidgetBuilder, WidgetMessage},
+        HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+
+// ANCHOR: main_menu_action
+pub enum MainMenuAction {
+    Start,
+    Quit,
+}
+// ANCHOR_END: main_menu_action
+
+// ANCHOR: main_menu_struct
+// The first thing that is shown after the start of the game, the gameplay scene is loaded only
+// when the player presses Start.
+pub struct MainMenu {
+    root: Handle<UiNode>,
+    start: Handle<UiNode>,
+    quit: Handle<UiNode>,
+    error_text: Handle<UiNode>,
+}
+// ANCHOR_END: main_menu_struct
+
+impl MainMenu {
+    // ANCHOR: main_menu_new
+    pub fn new(ui: &mut UserInterface) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let start = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .with_height(40.0)
+                .with_margin(Thickness::uniform(5.0)),
+        )
+        .with_text("Start")
+        .build(ctx);
+
+        let quit = ButtonBuilder::new(
+            WidgetBuilder::new()
+                .with_height(40.0)
+                .with_margin(Thickness::uniform(5.0)),
+        )
+        .with_text("Quit")
+        .build(ctx);
+
+        let error_text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_margin(Thickness::uniform(5.0))
+                .with_foreground(Brush::Solid(Color::RED)),
+        )
+        .with_wrap(WrapMode::Word)
+        .build(ctx);
+
+        let buttons = StackPanelBuilder::new(
+            WidgetBuilder::new()
+                .with_width(200.0)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_child(start)
+                .with_child(quit)
+                .with_child(error_text),
+        )
+        .build(ctx);
+
+        // The menu covers the entire screen, so the empty world is not visible behind it.
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_background(Brush::Solid(Color::from_rgba(20, 20, 20, 255)))
+                .with_child(buttons),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            start,
+            quit,
+            error_text,
+        }
+    }
+    // ANCHOR_END: main_menu_new
+
+    // ANCHOR: main_menu_message
+    pub fn handle_ui_message(&self, message: &UiMessage) -> Option<MainMenuAction> {
+        if let Some(ButtonMessage::Click) = message.data::<ButtonMessage>() {
+            if message.destination() == self.start {
+                return Some(MainMenuAction::Start);
+            } else if message.destination() == self.quit {
+                return Some(MainMenuAction::Quit);
+            }
+        }
+        None
+    }
+    // ANCHOR_END: main_menu_message
+
+    // ANCHOR: main_menu_state
+    pub fn set_visible(&mut self, ui: &mut UserInterface, visible: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+
+    // Shows a message under the buttons, for example when a level could not be loaded.
+    pub fn set_error(&mut self, ui: &mut UserInterface, error: Option<&str>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.error_text,
+            MessageDirection::ToWidget,
+            error.is_some(),
+        ));
+        ui.send_message(TextMessage::text(
+            self.error_text,
+            MessageDirection::ToWidget,
+            error.unwrap_or_default().to_string(),
+        ));
+    }
+    // ANCHOR_END: main_menu_state
+}
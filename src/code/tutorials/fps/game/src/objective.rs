@@ -0,0 +1,102 @@
+use crate::Game;
+use fyrox::{
+    core::{
+        algebra::Vector3, reflect::prelude::*, type_traits::prelude::*,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+
+// ANCHOR: objective_markers
+// Identifies a marker, it stays valid until the marker is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkerId(u32);
+
+#[derive(Debug, Clone)]
+pub struct ObjectiveMarker {
+    pub id: MarkerId,
+    pub position: Vector3<f32>,
+    pub label: String,
+}
+
+// Places that the player should get to, they're shown on the screen and on the compass. Any
+// script could add markers through the plugin, the `Objective` script does it for a node.
+#[derive(Default, Debug)]
+pub struct ObjectiveMarkers {
+    markers: Vec<ObjectiveMarker>,
+    next_id: u32,
+}
+
+impl ObjectiveMarkers {
+    pub fn add(&mut self, position: Vector3<f32>, label: String) -> MarkerId {
+        let id = MarkerId(self.next_id);
+        self.next_id += 1;
+        self.markers.push(ObjectiveMarker {
+            id,
+            position,
+            label,
+        });
+        id
+    }
+
+    // Returns false if there's no such marker.
+    pub fn remove(&mut self, id: MarkerId) -> bool {
+        let count = self.markers.len();
+        self.markers.retain(|marker| marker.id != id);
+        self.markers.len() != count
+    }
+
+    pub fn set_position(&mut self, id: MarkerId, position: Vector3<f32>) {
+        if let Some(marker) = self.markers.iter_mut().find(|marker| marker.id == id) {
+            marker.position = position;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ObjectiveMarker> {
+        self.markers.iter()
+    }
+}
+// ANCHOR_END: objective_markers
+
+// ANCHOR: objective
+// Adds a marker at the position of the node, it follows the node if it moves. Remove the node when
+// the objective is done.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "db0b8fd0-b932-48fc-baea-e38592ebeb6b")]
+#[visit(optional)]
+pub struct Objective {
+    // A text that is shown next to the marker.
+    label: InheritableVariable<String>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    marker: Option<MarkerId>,
+}
+
+impl ScriptTrait for Objective {
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        let position = ctx.scene.graph[ctx.handle].global_position();
+        let game = ctx.plugins.get_mut::<Game>();
+        self.marker = Some(game.objective_markers.add(position, (*self.label).clone()));
+    }
+
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        if let Some(marker) = self.marker.take() {
+            ctx.plugins
+                .get_mut::<Game>()
+                .objective_markers
+                .remove(marker);
+        }
+    }
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if let Some(marker) = self.marker {
+            let position = ctx.scene.graph[ctx.handle].global_position();
+            ctx.plugins
+                .get_mut::<Game>()
+                .objective_markers
+                .set_position(marker, position);
+        }
+    }
+}
+// ANCHOR_END: objective
@@ -0,0 +1,102 @@
+use crate::{item::ItemId, player::Player, trigger::find_player_in_trigger};
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    script::{ScriptContext, ScriptTrait},
+};
+
+// ANCHOR: pickup_kind
+#[derive(Visit, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickupKind {
+    #[default]
+    Health,
+    Ammo,
+    // Gives the item that is set in the pickup.
+    Item,
+}
+// ANCHOR_END: pickup_kind
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "3834d228-a8be-4783-870d-918a6144b888")]
+#[visit(optional)]
+pub struct Pickup {
+    // ANCHOR: pickup_fields
+    kind: InheritableVariable<PickupKind>,
+
+    // Amount of health or rounds of reserve ammo that the pickup gives.
+    amount: InheritableVariable<f32>,
+
+    // An item that is given by the pickup of the `Item` kind.
+    item: InheritableVariable<ItemId>,
+
+    // How fast (in degrees per second) the pickup spins around its vertical axis.
+    rotation_speed: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    angle: f32,
+    // ANCHOR_END: pickup_fields
+}
+
+impl Default for Pickup {
+    fn default() -> Self {
+        Self {
+            kind: Default::default(),
+            amount: 25.0.into(),
+            item: Default::default(),
+            rotation_speed: 90.0.into(),
+            angle: 0.0,
+        }
+    }
+}
+
+impl Pickup {
+    // ANCHOR: apply
+    // Returns `true` if the pickup was used. The player cannot pick up health, if it is not hurt,
+    // and an item that it already has.
+    fn apply(&self, player: &mut Player) -> bool {
+        match *self.kind {
+            PickupKind::Health => player.heal(*self.amount),
+            PickupKind::Ammo => {
+                player.add_reserve_ammo(*self.amount as u32);
+                true
+            }
+            PickupKind::Item => player.add_item(*self.item),
+        }
+    }
+    // ANCHOR_END: apply
+}
+
+impl ScriptTrait for Pickup {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        // ANCHOR: rotation
+        self.angle = (self.angle + *self.rotation_speed * ctx.dt) % 360.0;
+        ctx.scene.graph[ctx.handle]
+            .local_transform_mut()
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::y_axis(),
+                self.angle.to_radians(),
+            ));
+        // ANCHOR_END: rotation
+
+        // ANCHOR: pick_up
+        let graph = &mut ctx.scene.graph;
+        let Some(body) = find_player_in_trigger(graph, ctx.handle) else {
+            return;
+        };
+
+        let used = graph
+            .try_get_mut(body)
+            .and_then(|body| body.try_get_script_mut::<Player>())
+            .map_or(false, |player| self.apply(player));
+
+        if used {
+            graph.remove_node(ctx.handle);
+        }
+        // ANCHOR_END: pick_up
+    }
+}
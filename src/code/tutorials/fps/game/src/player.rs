@@ -1,66 +1,3956 @@
-use crate::weapon::ShootWeaponMessage;
+use crate::{
+    effect::spawn_effect,
+    enemy::Enemy,
+    grenade::Grenade,
+    hazard::find_hazard,
+    health::find_health_mut,
+    interaction::{find_interaction, InteractMessage},
+    item::ItemId,
+    key_bindings::KeyBindings,
+    player_config::PlayerConfig,
+    player_state::PlayerState,
+    sound::play_sound,
+    surface::{find_surface_kind, SurfaceEffects, SurfaceSounds},
+    weapon::{ShootWeaponMessage, WeaponSlot},
+    Game,
+};
 use fyrox::{
     core::{
-        algebra::{UnitQuaternion, UnitVector3, Vector3},
+        algebra::{Point3, UnitQuaternion, UnitVector3, Vector2, Vector3},
+        color::Color,
+        log::Log,
+        math,
         pool::Handle,
         reflect::prelude::*,
         type_traits::prelude::*,
         variable::InheritableVariable,
         visitor::prelude::*,
     },
-    event::{DeviceEvent, ElementState, Event, MouseButton, WindowEvent},
-    keyboard::{KeyCode, PhysicalKey},
-    scene::{node::Node, rigidbody::RigidBody},
+    engine::GraphicsContext,
+    event::{DeviceEvent, ElementState, Event, MouseButton, MouseScrollDelta, WindowEvent},
+    gui::message::KeyCode,
+    keyboard::PhysicalKey,
+    rand::{seq::SliceRandom, thread_rng, Rng},
+    resource::{
+        model::{ModelResource, ModelResourceExtension},
+        texture::TextureResource,
+    },
+    scene::{
+        camera::{Camera, Projection},
+        collider::{Collider, ColliderShape},
+        debug::Line,
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
+        node::Node,
+        ragdoll::Ragdoll,
+        rigidbody::{RigidBody, RigidBodyType},
+        sound::SoundBufferResource,
+    },
     script::{ScriptContext, ScriptTrait},
+    utils::translate_key_to_ui,
+    window::CursorGrabMode,
 };
 
-#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
-#[type_uuid(id = "49cfe29e-c7c7-4317-8178-996251a0c2f9")]
-#[visit(optional)]
-pub struct Player {
-    // ANCHOR: input_fields
-    #[visit(optional)]
-    #[reflect(hidden)]
-    move_forward: bool,
+// ANCHOR: fire_mode
+#[derive(Visit, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FireMode {
+    // Shots hit instantly where the player looks.
+    #[default]
+    HitScan,
+    // Shots launch a physical projectile that flies until it hits something.
+    Projectile,
+}
+// ANCHOR_END: fire_mode
+
+// ANCHOR: tracer
+// A line from the muzzle to the point where a shot has hit something.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+struct Tracer {
+    begin: Vector3<f32>,
+    end: Vector3<f32>,
+    frames_left: u32,
+}
+// ANCHOR_END: tracer
+
+// ANCHOR: damage_indicator
+// Shows the direction to something that has hurt the player.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+struct DamageIndicator {
+    // World position of the attacker.
+    source: Vector3<f32>,
+    // Angle (in degrees) from the view direction to the source, positive angles are to the right.
+    angle: f32,
+    time_left: f32,
+}
+// ANCHOR_END: damage_indicator
+
+// ANCHOR: damage_number
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+struct DamageNumber {
+    position: Vector3<f32>,
+    amount: f32,
+    time_left: f32,
+}
+// ANCHOR_END: damage_number
+
+// Maximum distance of a hit scan shot.
+const SHOT_RANGE: f32 = 1000.0;
+
+// A piercing shot continues this far behind the point of a hit, so the next ray does not hit the
+// same surface again.
+const PENETRATION_STEP: f32 = 0.01;
+
+// For how long (in seconds) a damage indicator stays on the screen.
+const DAMAGE_INDICATOR_DURATION: f32 = 1.5;
+
+// For how long (in seconds) a hit marker and damage numbers stay on the screen.
+const HIT_MARKER_DURATION: f32 = 0.2;
+const DAMAGE_NUMBER_DURATION: f32 = 1.0;
+
+// For how many frames a tracer stays visible.
+const TRACER_FRAMES: u32 = 3;
+
+// Maximum number of decals in the scene, the oldest ones are reused when there's too many.
+const MAX_DECALS: usize = 32;
+
+// For how long (in seconds) the muzzle flash stays visible after a shot.
+const MUZZLE_FLASH_DURATION: f32 = 0.05;
+
+// Horizontal speed at which footsteps start to play.
+const FOOTSTEP_SPEED_THRESHOLD: f32 = 0.5;
+
+// How far the camera dips per one unit of landing speed, and the deepest possible dip.
+const LANDING_DIP_SCALE: f32 = 0.02;
+const MAX_LANDING_DIP: f32 = 0.3;
+
+// Surfaces with the Y component of the normal less than this are considered vertical (walls,
+// step edges), and surfaces with the Y component greater than this - horizontal (floors).
+const VERTICAL_SURFACE_NORMAL_Y: f32 = 0.3;
+const HORIZONTAL_SURFACE_NORMAL_Y: f32 = 0.7;
+
+// Time (in seconds) after a jump, during which the player is not snapped to the ground.
+const GROUND_SNAP_JUMP_DELAY: f32 = 0.3;
+
+// Limits of the field of view (in degrees) of the camera.
+const MIN_FOV: f32 = 10.0;
+const MAX_FOV: f32 = 120.0;
+
+// Gravity is much weaker in water, buoyancy compensates the rest of it.
+const WATER_GRAVITY_SCALE: f32 = 0.2;
+
+// The player climbs down a ladder when it looks down more than this angle (in degrees).
+const LADDER_DESCEND_PITCH: f32 = 30.0;
+
+// Horizontal speed that is given to the player when it climbs off the top of a ladder.
+const LADDER_EXIT_SPEED: f32 = 2.0;
+
+// Maximum distance from the body to a wall, at which the player can run on it.
+const WALL_RUN_CHECK_DISTANCE: f32 = 0.8;
+
+// Gravity is weaker while running on a wall, so the player slowly slides down.
+const WALL_RUN_GRAVITY_SCALE: f32 = 0.25;
+
+// Horizontal speed with which a jump pushes the player away from a wall.
+const WALL_JUMP_SPEED: f32 = 5.0;
+
+// The grapple releases when the player gets this close to its anchor.
+const GRAPPLE_RELEASE_DISTANCE: f32 = 1.5;
+
+// Damping of the grapple spring, without it the player would swing around the anchor forever.
+const GRAPPLE_DAMPING: f32 = 2.0;
+
+// Distance in front of the capsule at which a ledge is searched for.
+const MANTLE_REACH: f32 = 0.3;
+
+// A slide ends when the horizontal speed of the player drops below this value.
+const SLIDE_MIN_SPEED: f32 = 2.0;
+
+// The third-person camera is kept this far from obstacles, so it won't clip through them.
+const CAMERA_COLLISION_MARGIN: f32 = 0.2;
+
+// Distance, height and rotation speed (in degrees per second) of the camera orbiting the place of
+// death.
+const DEATH_CAM_DISTANCE: f32 = 3.0;
+const DEATH_CAM_HEIGHT: f32 = 2.0;
+const DEATH_CAM_ORBIT_SPEED: f32 = 30.0;
+
+// The spectator camera stays at this distance behind the target, looking at a point at this
+// height above it.
+const SPECTATE_DISTANCE: f32 = 4.0;
+const SPECTATE_HEIGHT: f32 = 1.5;
+// How fast the spectator camera catches up with the target.
+const SPECTATE_SMOOTHING: f32 = 5.0;
+
+// Horizontal speed at which head bobbing starts.
+const BOB_SPEED_THRESHOLD: f32 = 0.1;
+
+// Turning speed (in degrees per second) and strafing speed at which the weapon sways the most.
+const SWAY_MAX_LOOK_RATE: f32 = 360.0;
+const SWAY_MAX_STRAFE_SPEED: f32 = 5.0;
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "49cfe29e-c7c7-4317-8178-996251a0c2f9")]
+#[visit(optional)]
+pub struct Player {
+    // ANCHOR: config_field
+    #[visit(optional)]
+    pub config: PlayerConfig,
+    // ANCHOR_END: config_field
+
+    // ANCHOR: input_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    move_forward: bool,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    move_backward: bool,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    move_left: bool,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    move_right: bool,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    yaw: f32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    pitch: f32,
+    // ANCHOR_END: input_fields
+
+    // ANCHOR: camera_field
+    #[visit(optional)]
+    camera: Handle<Node>,
+    // ANCHOR_END: camera_field
+
+    // ANCHOR: current_weapon_field
+    #[visit(optional)]
+    current_weapon: InheritableVariable<Handle<Node>>,
+    // ANCHOR_END: current_weapon_field
+
+    // ANCHOR: weapon_slots_fields
+    // All the weapons that the player carries, only one of them is selected at a time. When the
+    // list is empty, the current weapon above becomes the only slot. After that the current weapon
+    // is the weapon node of the selected slot, it is updated on each shot.
+    #[visit(optional)]
+    weapons: Vec<WeaponSlot>,
+
+    // Index of the selected weapon in the list above.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    selected_weapon: usize,
+    // ANCHOR_END: weapon_slots_fields
+
+    // ANCHOR: weapon_sway_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    sway_offset: Vector3<f32>,
+
+    // Camera angles from the previous frame, they are used to find how fast the player turns.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    sway_prev_yaw: f32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    sway_prev_pitch: f32,
+    // ANCHOR_END: weapon_sway_fields
+
+    // ANCHOR: spread_fields
+    // Spread added by the recent shots and the total spread of the next shot (in degrees).
+    #[visit(optional)]
+    #[reflect(hidden)]
+    shot_spread: f32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    spread: f32,
+    // ANCHOR_END: spread_fields
+
+    // ANCHOR: ads_fields
+    // How far the weapon model has moved from the hip to the sights, in [0; 1] range.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    ads_factor: f32,
+    // ANCHOR_END: ads_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    shoot: bool,
+
+    // ANCHOR: fire_held_field
+    // State of the fire button. `shoot` is set from it on each update, it is true only when the
+    // weapon fires, so the weapon is asked to shoot exactly at the moments of the shots.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    fire_held: bool,
+    // ANCHOR_END: fire_held_field
+
+    // ANCHOR: action_buffer_field
+    #[visit(optional)]
+    #[reflect(hidden)]
+    action_buffer: ActionBuffer,
+    // ANCHOR_END: action_buffer_field
+
+    // ANCHOR: jump_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    jump: bool,
+    // ANCHOR_END: jump_fields
+
+    // ANCHOR: jump_timing_fields
+    // Time left to jump without ground, negative when jumping is not allowed.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    coyote_timer: f32,
+
+    // Time left for the last jump press, negative when there's no pending press.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    jump_buffer_timer: f32,
+    // ANCHOR_END: jump_timing_fields
+
+    // ANCHOR: air_jump_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    air_jumps_used: u32,
+    // ANCHOR_END: air_jump_fields
+
+    // ANCHOR: launch_fields
+    // Velocity that something (a jump pad, for example) has given to the player. It is applied
+    // at the next update.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    launch_velocity: Option<Vector3<f32>>,
+
+    // Set by launches that are allowed to exceed the max speed, it is reset on landing.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    exceed_max_speed: bool,
+    // ANCHOR_END: launch_fields
+
+    // ANCHOR: cutscene_fields
+    // The player cannot be controlled while a cutscene is playing.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    cutscene: bool,
+    // ANCHOR_END: cutscene_fields
+
+    // ANCHOR: dash_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    dash: bool,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    dash_timer: f32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    dash_cooldown_timer: f32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    dash_direction: Vector3<f32>,
+    // ANCHOR_END: dash_fields
+
+    // ANCHOR: wall_run_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    wall_run_timer: f32,
+    // ANCHOR_END: wall_run_fields
+
+    // ANCHOR: grapple_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    grapple: bool,
+
+    // A point the grappling hook is attached to.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    grapple_anchor: Option<Vector3<f32>>,
+    // ANCHOR_END: grapple_fields
+
+    // ANCHOR: mantle_fields
+    // Position of the body on top of the ledge, it is set while the player climbs onto it.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    mantle_target: Option<Vector3<f32>>,
+
+    // Position of the body where the climbing has started.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    mantle_start: Vector3<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    mantle_elapsed: f32,
+    // ANCHOR_END: mantle_fields
+
+    // ANCHOR: sprint_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    sprint: bool,
+    // ANCHOR_END: sprint_fields
+
+    // ANCHOR: stamina_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    stamina: f32,
+
+    // Set when stamina runs out, sprinting is not possible until it recovers.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    exhausted: bool,
+    // ANCHOR_END: stamina_fields
+
+    // ANCHOR: crouch_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    crouch: bool,
+
+    // Current crouching progress, where 0.0 - standing, 1.0 - fully crouched.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    crouch_factor: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    collider: Handle<Node>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    stand_camera_height: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    stand_capsule_length: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    stand_capsule_top: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    capsule_bottom: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    capsule_radius: f32,
+    // ANCHOR_END: crouch_fields
+
+    // ANCHOR: slide_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    sliding: bool,
+
+    // The state of the crouch key at the previous frame, a slide starts only when it is pressed.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    slide_crouch_held: bool,
+    // ANCHOR_END: slide_fields
+
+    // ANCHOR: head_bob_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    bob_phase: f32,
+
+    // Fades the head bob in and out when the player starts or stops moving.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    bob_weight: f32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    bob_offset: f32,
+
+    // How far the current step has gone, from 0 when a foot is planted to 1 right before the
+    // next step.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    step_progress: f32,
+
+    // Either 1 or -1, it flips on each step, so the weapon swings to the other side.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    step_side: f32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    weapon_bob_weight: f32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    weapon_bob_offset: Vector3<f32>,
+    // ANCHOR_END: head_bob_fields
+
+    // ANCHOR: axis_fields
+    // Combined movement input from all sources, where X - strafing, Y - forward movement.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    move_axis: Vector2<f32>,
+
+    // Look input from the gamepad, where X - yaw, Y - pitch.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    look_axis: Vector2<f32>,
+    // ANCHOR_END: axis_fields
+
+    // ANCHOR: wish_dir_field
+    // Desired movement direction in world space, calculated from all input sources.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    wish_dir: Vector3<f32>,
+    // ANCHOR_END: wish_dir_field
+
+    // ANCHOR: ground_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    grounded: bool,
+
+    // Ground state at the previous frame, used to detect landings.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    was_grounded: bool,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    ground_normal: Vector3<f32>,
+
+    // A collider the player stands on, it defines the sound of footsteps.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    ground_collider: Handle<Node>,
+
+    // Time left until ground snapping is allowed again after a jump.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    ground_snap_delay: f32,
+    // ANCHOR_END: ground_fields
+
+    // ANCHOR: platform_fields
+    // A moving body the player stands on and its position at the previous frame.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    platform: Handle<Node>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    platform_position: Vector3<f32>,
+    // ANCHOR_END: platform_fields
+
+    // ANCHOR: contact_fields
+    // Colliders that touched the player at the previous frame, they're used to find new contacts.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    contacts: Vec<Handle<Node>>,
+    // ANCHOR_END: contact_fields
+
+    // ANCHOR: crosshair_field
+    #[visit(optional)]
+    crosshair_texture: InheritableVariable<Option<TextureResource>>,
+    // ANCHOR_END: crosshair_field
+
+    // ANCHOR: interaction_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    interact: bool,
+
+    // An object the player currently looks at.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    interaction_target: Handle<Node>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    interaction_prompt: Option<String>,
+    // ANCHOR_END: interaction_fields
+
+    // ANCHOR: shooting_fields
+    // Time (in seconds) until the next shot is allowed.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    shot_timer: f32,
+
+    // A prefab that will be instantiated at the point where a shot hits something.
+    #[visit(optional)]
+    impact_marker: InheritableVariable<Option<ModelResource>>,
+
+    // A random sound from this list will be played where a shot hits something, unless there's a
+    // specific set for the surface that was hit.
+    #[visit(optional)]
+    impact_sounds: InheritableVariable<Vec<SoundBufferResource>>,
+
+    #[visit(optional)]
+    surface_impact_sounds: InheritableVariable<SurfaceSounds>,
+    // ANCHOR_END: shooting_fields
+
+    // ANCHOR: effect_fields
+    // Effects (usually particle systems) of the shots: smoke at the muzzle and sparks or dust where
+    // a shot hits something. Effects could be specific to the surface, just like the sounds.
+    #[visit(optional)]
+    muzzle_effect: InheritableVariable<Option<ModelResource>>,
+
+    #[visit(optional)]
+    impact_effect: InheritableVariable<Option<ModelResource>>,
+
+    #[visit(optional)]
+    surface_impact_effects: InheritableVariable<SurfaceEffects>,
+    // ANCHOR_END: effect_fields
+
+    // ANCHOR: decal_fields
+    // A prefab with a decal, that will be left at the point where a shot hits something.
+    #[visit(optional)]
+    decal_prefab: InheritableVariable<Option<ModelResource>>,
+
+    // Instances of the decal prefab, they are moved to new places instead of creating new ones.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    decals: Vec<Handle<Node>>,
+
+    // Index of the decal that will be reused next, it is always the oldest one.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    next_decal: usize,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    tracers: Vec<Tracer>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    hit_marker_timer: f32,
+
+    // Set if the last hit has killed the target.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    hit_marker_kill: bool,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    damage_numbers: Vec<DamageNumber>,
+    // ANCHOR_END: decal_fields
+
+    // ANCHOR: projectile_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    fire_mode: FireMode,
+
+    // A prefab that will be launched in the projectile fire mode.
+    #[visit(optional)]
+    pub projectile_prefab: Option<ModelResource>,
+    // ANCHOR_END: projectile_fields
+
+    // ANCHOR: inventory_fields
+    // Items that the player has collected, for example keys that open locked doors.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    items: Vec<ItemId>,
+    // ANCHOR_END: inventory_fields
+
+    // ANCHOR: ammo_fields
+    // Rounds that could be used to refill the magazine.
+    #[visit(optional)]
+    pub reserve_ammo: u32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    reload_timer: f32,
+    // ANCHOR_END: ammo_fields
+
+    // ANCHOR: grenade_fields
+    // A prefab with the `Grenade` script.
+    #[visit(optional)]
+    pub grenade_prefab: Option<ModelResource>,
+
+    // Grenades that the player carries.
+    #[visit(optional)]
+    pub grenade_count: u32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    grenade: bool,
+
+    // Time for which the grenade key is held, it is set only while a grenade is cooked.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    grenade_cook: Option<f32>,
+    // ANCHOR_END: grenade_fields
+
+    // ANCHOR: recoil_fields
+    // Current recoil offset of the camera, where X - yaw, Y - pitch.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    recoil_offset: Vector2<f32>,
+    // ANCHOR_END: recoil_fields
+
+    // ANCHOR: shake_fields
+    // Amount of the screen shake from 0 to 1, it decays over time.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    trauma: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shake_time: f32,
+
+    // Rotation of the camera on top of the aim, it is excluded when shooting.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shake_rotation: UnitQuaternion<f32>,
+    // ANCHOR_END: shake_fields
+
+    // ANCHOR: camera_tilt_fields
+    // Current roll and forward tilt of the camera in degrees.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    camera_tilt: Vector2<f32>,
+    // ANCHOR_END: camera_tilt_fields
+
+    // ANCHOR: muzzle_flash_fields
+    // A light source (or any other node) that will be enabled for a short time on each shot.
+    #[visit(optional)]
+    muzzle_flash: InheritableVariable<Handle<Node>>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    muzzle_flash_timer: f32,
+    // ANCHOR_END: muzzle_flash_fields
+
+    // ANCHOR: melee_fields
+    // A sound that is played on each melee attack, even if it did not hit anything.
+    #[visit(optional)]
+    melee_sound: InheritableVariable<Option<SoundBufferResource>>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    melee: bool,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    melee_timer: f32,
+    // ANCHOR_END: melee_fields
+
+    // ANCHOR: flashlight_fields
+    // A spot light, that is usually a child of the camera.
+    #[visit(optional)]
+    flashlight: InheritableVariable<Handle<Node>>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    flashlight_on: bool,
+
+    // Seconds of light left in the battery.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    battery: f32,
+    // ANCHOR_END: flashlight_fields
+
+    // ANCHOR: third_person_fields
+    // Visual model of the character, it is shown only in the third-person mode.
+    #[visit(optional)]
+    model: InheritableVariable<Handle<Node>>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    third_person: bool,
+
+    // Direction (in degrees) the character model faces.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    model_yaw: f32,
+
+    // Local position of the camera in the first-person mode.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    first_person_camera_position: Vector3<f32>,
+    // ANCHOR_END: third_person_fields
+
+    // ANCHOR: health_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    health: f32,
+
+    // A node at which the player respawns. If not set, the player respawns at its start position.
+    #[visit(optional)]
+    spawn_point: InheritableVariable<Handle<Node>>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    start_position: Vector3<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    damage_indicators: Vec<DamageIndicator>,
+    // ANCHOR_END: health_fields
+
+    // ANCHOR: death_cam_fields
+    // An optional ragdoll, that is activated when the player dies.
+    #[visit(optional)]
+    ragdoll: InheritableVariable<Handle<Node>>,
+
+    // Time left until respawn, it is set only while the player is dead.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    death_timer: Option<f32>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    death_cam_angle: f32,
+    // ANCHOR_END: death_cam_fields
+
+    // ANCHOR: spectate_fields
+    // In the spectator mode the camera follows enemies and other players, while the player
+    // stands still.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    spectating: bool,
+
+    // The followed node, when there's none the camera flies freely.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    spectate_target: Handle<Node>,
+
+    // Position of the target in the list of targets. When the target is gone, the next one takes
+    // its place in the list.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    spectate_index: usize,
+
+    // World position of the spectator camera.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    spectate_position: Vector3<f32>,
+    // ANCHOR_END: spectate_fields
+
+    // ANCHOR: checkpoint_fields
+    // Position and yaw of the player at the last reached checkpoint, it has priority over the
+    // spawn point.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    last_checkpoint: Option<Vector3<f32>>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    checkpoint_yaw: f32,
+    // ANCHOR_END: checkpoint_fields
+
+    // ANCHOR: swimming_fields
+    // Number of water volumes the player is in. Volumes could overlap, so a simple flag is not
+    // enough.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    water_volumes: u32,
+    // ANCHOR_END: swimming_fields
+
+    // ANCHOR: ladder_fields
+    #[visit(skip)]
+    #[reflect(hidden)]
+    ladders: u32,
+
+    // Vertical velocity the player had on a ladder at the previous frame.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    climb_velocity: f32,
+    // ANCHOR_END: ladder_fields
+
+    // ANCHOR: footstep_fields
+    // A random sound from this list will be played on each step.
+    #[visit(optional)]
+    footstep_sounds: InheritableVariable<Vec<SoundBufferResource>>,
+
+    // Footstep sounds of specific surfaces, the list above is used for the rest of them.
+    #[visit(optional)]
+    surface_footstep_sounds: InheritableVariable<SurfaceSounds>,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    footstep_timer: f32,
+    // ANCHOR_END: footstep_fields
+
+    // ANCHOR: landing_fields
+    #[visit(optional)]
+    landing_sound: InheritableVariable<Option<SoundBufferResource>>,
+
+    // The highest falling speed since the player left the ground.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    fall_speed: f32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    landing_dip: f32,
+    // ANCHOR_END: landing_fields
+
+    // ANCHOR: key_bindings_field
+    #[visit(optional)]
+    pub key_bindings: KeyBindings,
+    // ANCHOR_END: key_bindings_field
+
+    // ANCHOR: fov_fields
+    #[visit(optional)]
+    #[reflect(hidden)]
+    aim: bool,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    current_fov: f32,
+    // ANCHOR_END: fov_fields
+
+    // ANCHOR: look_smoothing_fields
+    // Camera angles that follow yaw and pitch, when smoothing is enabled.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    smooth_yaw: f32,
+
+    #[visit(optional)]
+    #[reflect(hidden)]
+    smooth_pitch: f32,
+    // ANCHOR_END: look_smoothing_fields
+
+    // ANCHOR: noclip_fields
+    // Debug mode, in which the player flies through walls.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    noclip: bool,
+    // ANCHOR_END: noclip_fields
+
+    // ANCHOR: pending_state_field
+    // A loaded state that will be applied to the rigid body on the next update.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pending_state: Option<PlayerState>,
+    // ANCHOR_END: pending_state_field
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            config: Default::default(),
+            move_forward: false,
+            move_backward: false,
+            move_left: false,
+            move_right: false,
+            yaw: 0.0,
+            pitch: 0.0,
+            camera: Default::default(),
+            weapons: Default::default(),
+            current_weapon: Default::default(),
+            selected_weapon: 0,
+            sway_offset: Default::default(),
+            ads_factor: 0.0,
+            shot_spread: 0.0,
+            spread: 0.0,
+            sway_prev_yaw: 0.0,
+            sway_prev_pitch: 0.0,
+            shoot: false,
+            fire_held: false,
+            jump: false,
+            coyote_timer: -1.0,
+            jump_buffer_timer: -1.0,
+            action_buffer: Default::default(),
+            air_jumps_used: 0,
+            launch_velocity: None,
+            exceed_max_speed: false,
+            cutscene: false,
+            dash: false,
+            dash_timer: 0.0,
+            dash_cooldown_timer: 0.0,
+            dash_direction: Default::default(),
+            wall_run_timer: 0.0,
+            grapple: false,
+            grapple_anchor: None,
+            mantle_target: None,
+            mantle_start: Default::default(),
+            mantle_elapsed: 0.0,
+            sprint: false,
+            crouch: false,
+            stamina: 100.0,
+            exhausted: false,
+            crouch_factor: 0.0,
+            collider: Default::default(),
+            stand_camera_height: 0.0,
+            stand_capsule_length: 0.0,
+            stand_capsule_top: 0.0,
+            capsule_bottom: 0.0,
+            capsule_radius: 0.0,
+            sliding: false,
+            slide_crouch_held: false,
+            bob_phase: 0.0,
+            bob_weight: 0.0,
+            bob_offset: 0.0,
+            step_progress: 0.0,
+            step_side: 1.0,
+            weapon_bob_weight: 0.0,
+            weapon_bob_offset: Default::default(),
+            move_axis: Default::default(),
+            look_axis: Default::default(),
+            wish_dir: Default::default(),
+            grounded: false,
+            was_grounded: false,
+            ground_normal: Vector3::y(),
+            ground_collider: Default::default(),
+            ground_snap_delay: 0.0,
+            platform: Default::default(),
+            platform_position: Default::default(),
+            contacts: Default::default(),
+            crosshair_texture: Default::default(),
+            interact: false,
+            interaction_target: Default::default(),
+            interaction_prompt: None,
+            shot_timer: 0.0,
+            impact_marker: Default::default(),
+            impact_sounds: Default::default(),
+            surface_impact_sounds: Default::default(),
+            muzzle_effect: Default::default(),
+            impact_effect: Default::default(),
+            surface_impact_effects: Default::default(),
+            decal_prefab: Default::default(),
+            decals: Default::default(),
+            next_decal: 0,
+            tracers: Default::default(),
+            hit_marker_timer: 0.0,
+            hit_marker_kill: false,
+            damage_numbers: Default::default(),
+            fire_mode: Default::default(),
+            projectile_prefab: None,
+            items: Default::default(),
+            reserve_ammo: 90,
+            grenade_prefab: None,
+            grenade_count: 3,
+            grenade: false,
+            grenade_cook: None,
+            reload_timer: 0.0,
+            recoil_offset: Default::default(),
+            trauma: 0.0,
+            shake_time: 0.0,
+            shake_rotation: Default::default(),
+            camera_tilt: Default::default(),
+            muzzle_flash: Default::default(),
+            muzzle_flash_timer: 0.0,
+            melee_sound: Default::default(),
+            melee: false,
+            melee_timer: 0.0,
+            flashlight: Default::default(),
+            flashlight_on: false,
+            battery: 120.0,
+            model: Default::default(),
+            third_person: false,
+            ragdoll: Default::default(),
+            death_timer: None,
+            death_cam_angle: 0.0,
+            spectating: false,
+            spectate_target: Handle::NONE,
+            spectate_index: 0,
+            spectate_position: Default::default(),
+            model_yaw: 0.0,
+            first_person_camera_position: Default::default(),
+            health: 100.0,
+            spawn_point: Default::default(),
+            start_position: Default::default(),
+            damage_indicators: Default::default(),
+            footstep_sounds: Default::default(),
+            surface_footstep_sounds: Default::default(),
+            footstep_timer: 0.0,
+            landing_sound: Default::default(),
+            fall_speed: 0.0,
+            landing_dip: 0.0,
+            key_bindings: Default::default(),
+            aim: false,
+            current_fov: 75.0,
+            smooth_yaw: 0.0,
+            smooth_pitch: 0.0,
+            noclip: false,
+            pending_state: None,
+            last_checkpoint: None,
+            checkpoint_yaw: 0.0,
+            water_volumes: 0,
+            ladders: 0,
+            climb_velocity: 0.0,
+        }
+    }
+}
+
+// ANCHOR: cursor_grab
+// Grabbed cursor is hidden and cannot leave the window.
+fn set_cursor_grab(ctx: &mut ScriptContext, grab: bool) {
+    if let GraphicsContext::Initialized(ref graphics_context) = ctx.graphics_context {
+        let window = &graphics_context.window;
+        let mode = if grab {
+            CursorGrabMode::Confined
+        } else {
+            CursorGrabMode::None
+        };
+        Log::verify(window.set_cursor_grab(mode));
+        window.set_cursor_visible(!grab);
+    }
+}
+// ANCHOR_END: cursor_grab
+
+// ANCHOR: cast_ray_from_body
+// Casts a ray and returns the closest intersection with anything except the player itself.
+fn cast_ray_ignoring_player(
+    ctx: &ScriptContext,
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    max_len: f32,
+) -> Option<Intersection> {
+    let graph = &ctx.scene.graph;
+
+    let mut intersections = Vec::new();
+    graph.physics.cast_ray(
+        RayCastOptions {
+            ray_origin: Point3::from(origin),
+            ray_direction: direction,
+            max_len,
+            groups: Default::default(),
+            sort_results: true,
+        },
+        &mut intersections,
+    );
+
+    // Rays usually start inside the player's own capsule, so its colliders must be ignored.
+    intersections.into_iter().find(|intersection| {
+        graph
+            .try_get(intersection.collider)
+            .map_or(false, |collider| collider.parent() != ctx.handle)
+    })
+}
+
+// Casts a ray from the center of the player's body.
+fn cast_ray_from_body(
+    ctx: &ScriptContext,
+    direction: Vector3<f32>,
+    max_len: f32,
+) -> Option<Intersection> {
+    let origin = ctx.scene.graph.try_get(ctx.handle)?.global_position();
+    cast_ray_ignoring_player(ctx, origin, direction, max_len)
+}
+// ANCHOR_END: cast_ray_from_body
+
+// ANCHOR: random_direction_in_cone
+// A random direction that deviates from the given one by the angle (in radians) at most. All
+// directions in the cone are equally likely.
+fn random_direction_in_cone(direction: Vector3<f32>, angle: f32) -> Vector3<f32> {
+    if angle <= 0.0 {
+        return direction;
+    }
+
+    // Any two vectors perpendicular to the direction and to each other.
+    let side = direction
+        .cross(&Vector3::y())
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(Vector3::x);
+    let up = side.cross(&direction);
+
+    let mut rng = thread_rng();
+    let radius = angle.tan() * rng.gen::<f32>().sqrt();
+    let phi = rng.gen_range(0.0..std::f32::consts::TAU);
+    (direction + side.scale(radius * phi.cos()) + up.scale(radius * phi.sin()))
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(direction)
+}
+// ANCHOR_END: random_direction_in_cone
+
+// ANCHOR: calculate_wish_dir
+// Converts movement input into a world space direction using the basis vectors of the camera.
+// Analog input is kept as is, but the length of the result never exceeds one, so diagonal
+// movement is not faster than straight movement.
+fn calculate_wish_dir(
+    move_axis: Vector2<f32>,
+    look_vector: Vector3<f32>,
+    side_vector: Vector3<f32>,
+) -> Vector3<f32> {
+    let wish_dir = look_vector.scale(move_axis.y) - side_vector.scale(move_axis.x);
+    if wish_dir.norm() > 1.0 {
+        wish_dir.normalize()
+    } else {
+        wish_dir
+    }
+}
+// ANCHOR_END: calculate_wish_dir
+
+// ANCHOR: approach_velocity
+// Moves the horizontal velocity towards the target one, the rate tells how fast. Exponential
+// smoothing gives the same velocity after the same time at any frame rate, a linear step would be
+// faster at lower ones.
+fn approach_velocity(
+    current: Vector2<f32>,
+    target: Vector2<f32>,
+    rate: f32,
+    dt: f32,
+) -> Vector2<f32> {
+    let t = 1.0 - (-rate * dt).exp();
+    current + (target - current).scale(t)
+}
+// ANCHOR_END: approach_velocity
+
+// ANCHOR: air_accelerate
+// Quake-style air acceleration. Only the part of the velocity along the wish direction is
+// limited: `current = velocity · wish_dir`, and `max_speed - current` is the most that could be
+// added to it. The step is `acceleration * wish_speed * dt`, so the new velocity is
+// `velocity + wish_dir * min(step, max_speed - current)`. When the wish direction is almost
+// perpendicular to the velocity, `current` is close to zero, so the full step is added and the
+// total speed grows, that's what strafe-jumping relies on.
+fn air_accelerate(
+    velocity: Vector3<f32>,
+    wish_dir: Vector3<f32>,
+    wish_speed: f32,
+    max_speed: f32,
+    acceleration: f32,
+    dt: f32,
+) -> Vector3<f32> {
+    let current = velocity.dot(&wish_dir);
+    let add_speed = wish_speed.min(max_speed) - current;
+    if add_speed <= 0.0 {
+        return velocity;
+    }
+    let step = (acceleration * wish_speed * dt).min(add_speed);
+    velocity + wish_dir.scale(step)
+}
+// ANCHOR_END: air_accelerate
+
+// ANCHOR: weapon_slot_key
+// Number keys from 1 to 9 select the weapons in the order they're listed.
+fn weapon_slot_key(key: KeyCode) -> Option<usize> {
+    [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ]
+    .iter()
+    .position(|slot_key| *slot_key == key)
+}
+// ANCHOR_END: weapon_slot_key
+
+// ANCHOR: jump_timers
+// A jump happens when both windows are open: the player was on the ground recently and the
+// jump key was pressed recently.
+fn is_jump_allowed(coyote_timer: f32, jump_buffer_timer: f32) -> bool {
+    coyote_timer >= 0.0 && jump_buffer_timer >= 0.0
+}
+// ANCHOR_END: jump_timers
+
+// ANCHOR: shake_noise
+// Smooth pseudo-random noise in the [-1; 1] range, different seeds give unrelated curves.
+fn shake_noise(time: f32, seed: f32) -> f32 {
+    (time + seed).sin() * 0.6 + (time * 2.3 + seed * 1.7).sin() * 0.4
+}
+// ANCHOR_END: shake_noise
+
+// ANCHOR: action_buffer
+#[derive(Visit, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferedAction {
+    Fire,
+    Reload,
+}
+
+// Remembers the last pressed action, so it could be performed a bit later when it becomes
+// available. Only one action is kept, a newer one replaces the older.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct ActionBuffer {
+    // The action and the time (in seconds) when it was pressed.
+    pending: Option<(BufferedAction, f32)>,
+}
+
+impl ActionBuffer {
+    pub fn push(&mut self, action: BufferedAction, time: f32) {
+        self.pending = Some((action, time));
+    }
+
+    // Returns true if the given action was pressed not longer than `window` seconds ago and it
+    // is available now. Such action is consumed, so it is performed only once. Expired actions
+    // are dropped.
+    pub fn take(
+        &mut self,
+        action: BufferedAction,
+        time: f32,
+        window: f32,
+        available: bool,
+    ) -> bool {
+        let Some((pending, pressed_at)) = self.pending else {
+            return false;
+        };
+        if time - pressed_at > window {
+            self.pending = None;
+            return false;
+        }
+        if pending == action && available {
+            self.pending = None;
+            return true;
+        }
+        false
+    }
+
+    pub fn clear(&mut self) {
+        self.pending = None;
+    }
+}
+// ANCHOR_END: action_buffer
+
+impl Player {
+    // ANCHOR: take_damage
+    // The source is a world position of the attacker, it is shown on the HUD. Damage without a
+    // source (falling, for example) has no indicator.
+    pub fn take_damage(&mut self, amount: f32, source: Option<Vector3<f32>>) {
+        self.health = (self.health - amount).max(0.0);
+        if let Some(source) = source {
+            self.damage_indicators.push(DamageIndicator {
+                source,
+                angle: 0.0,
+                time_left: DAMAGE_INDICATOR_DURATION,
+            });
+        }
+    }
+
+    pub fn health(&self) -> f32 {
+        self.health
+    }
+
+    // Returns pairs of angles and opacities of the damage indicators.
+    pub fn damage_indicators(&self) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.damage_indicators.iter().map(|indicator| {
+            (
+                indicator.angle,
+                indicator.time_left / DAMAGE_INDICATOR_DURATION,
+            )
+        })
+    }
+
+    fn update_damage_indicators(&mut self, ctx: &mut ScriptContext) {
+        for indicator in self.damage_indicators.iter_mut() {
+            indicator.time_left -= ctx.dt;
+        }
+        self.damage_indicators
+            .retain(|indicator| indicator.time_left > 0.0);
+
+        let Some(camera) = ctx.scene.graph.try_get(self.camera) else {
+            return;
+        };
+        let position = camera.global_position();
+        let look_vector = camera.look_vector();
+        let side_vector = camera.side_vector();
+
+        // The angle is recalculated each frame, this way indicators follow the turns of the player.
+        for indicator in self.damage_indicators.iter_mut() {
+            let to_source = indicator.source - position;
+            // The side vector points to the left.
+            let right = -to_source.dot(&side_vector);
+            let forward = to_source.dot(&look_vector);
+            indicator.angle = right.atan2(forward).to_degrees();
+        }
+    }
+    // ANCHOR_END: take_damage
+
+    // ANCHOR: save_state
+    // The script and its node cannot be borrowed mutably at the same time, so the state of the
+    // rigid body is read here by its handle.
+    pub fn save_state(&self, graph: &Graph, handle: Handle<Node>) -> PlayerState {
+        let (position, velocity) = graph
+            .try_get_of_type::<RigidBody>(handle)
+            .map(|rigid_body| (rigid_body.global_position(), rigid_body.lin_vel()))
+            .unwrap_or_default();
+
+        PlayerState {
+            position,
+            velocity,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            health: self.health,
+            current_weapon: self.selected_weapon,
+            weapon_ammo: self.weapons.iter().map(|weapon| weapon.ammo).collect(),
+            reserve_ammo: self.reserve_ammo,
+            grenade_count: self.grenade_count,
+            items: self.items.clone(),
+        }
+    }
+
+    // Position and velocity are applied to the rigid body on the next update.
+    pub fn load_state(&mut self, state: PlayerState) {
+        self.yaw = state.yaw;
+        self.pitch = state.pitch.clamp(-89.9, 89.9);
+        self.smooth_yaw = self.yaw;
+        self.smooth_pitch = self.pitch;
+        self.load_inventory(&state);
+        self.recoil_offset = Default::default();
+        self.reset_fall();
+        self.pending_state = Some(state);
+    }
+
+    // Takes only the things that the player carries, the position in the world stays the same.
+    // It is used to move the player to another level.
+    pub fn load_inventory(&mut self, state: &PlayerState) {
+        self.health = state.health.min(self.config.max_health);
+        if state.current_weapon < self.weapons.len() {
+            self.selected_weapon = state.current_weapon;
+        }
+        for (weapon, ammo) in self.weapons.iter_mut().zip(state.weapon_ammo.iter()) {
+            weapon.ammo = (*ammo).min(weapon.magazine_size);
+        }
+        self.reserve_ammo = state.reserve_ammo;
+        self.grenade_count = state.grenade_count;
+        self.items = state.items.clone();
+        self.reload_timer = 0.0;
+    }
+
+    fn apply_pending_state(&mut self, ctx: &mut ScriptContext) {
+        let Some(state) = self.pending_state.take() else {
+            return;
+        };
+
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            rigid_body
+                .local_transform_mut()
+                .set_position(state.position);
+            rigid_body.set_lin_vel(state.velocity);
+            rigid_body.set_ang_vel(Default::default());
+        }
+
+        // The loaded state could have another weapon selected.
+        self.update_weapon_models(&mut ctx.scene.graph);
+    }
+    // ANCHOR_END: save_state
+
+    // ANCHOR: set_checkpoint
+    pub fn set_checkpoint(&mut self, position: Vector3<f32>) {
+        self.last_checkpoint = Some(position);
+        self.checkpoint_yaw = self.yaw;
+    }
+    // ANCHOR_END: set_checkpoint
+
+    // ANCHOR: on_teleported
+    // Must be called after the body of the player was moved by a teleporter, which has turned the
+    // player by the given angle (in degrees).
+    pub fn on_teleported(&mut self, yaw_delta: f32) {
+        // The camera must turn at once, smoothing and sway would show the turn.
+        self.yaw += yaw_delta;
+        self.smooth_yaw += yaw_delta;
+        self.sway_prev_yaw += yaw_delta;
+        self.model_yaw += yaw_delta;
+        self.platform = Handle::NONE;
+        self.mantle_target = None;
+        self.reset_fall();
+    }
+    // ANCHOR_END: on_teleported
+
+    // ANCHOR: set_cutscene
+    pub fn set_cutscene(&mut self, active: bool) {
+        self.cutscene = active;
+        // Keys could be released during the cutscene, when they're ignored.
+        self.reset_input();
+    }
+    // ANCHOR_END: set_cutscene
+
+    // ANCHOR: launch
+    // Throws the player, the vertical velocity is replaced and the horizontal one is added to the
+    // current velocity. If `exceed_max_speed` is set, the player could move faster than the max
+    // speed until it lands.
+    pub fn launch(&mut self, velocity: Vector3<f32>, exceed_max_speed: bool) {
+        self.launch_velocity = Some(velocity);
+        self.exceed_max_speed |= exceed_max_speed;
+    }
+    // ANCHOR_END: launch
+
+    // ANCHOR: water
+    pub fn enter_water(&mut self) {
+        self.water_volumes += 1;
+    }
+
+    pub fn exit_water(&mut self) {
+        self.water_volumes = self.water_volumes.saturating_sub(1);
+    }
+
+    pub fn is_swimming(&self) -> bool {
+        self.water_volumes > 0
+    }
+    // ANCHOR_END: water
+
+    // ANCHOR: ladder
+    pub fn enter_ladder(&mut self) {
+        self.ladders += 1;
+    }
+
+    pub fn exit_ladder(&mut self) {
+        self.ladders = self.ladders.saturating_sub(1);
+    }
+
+    pub fn is_on_ladder(&self) -> bool {
+        self.ladders > 0
+    }
+    // ANCHOR_END: ladder
+
+    // ANCHOR: pickup_effects
+    // Returns `true` if the player was actually healed. Health never goes above the maximum.
+    pub fn heal(&mut self, amount: f32) -> bool {
+        if self.health >= self.config.max_health {
+            return false;
+        }
+        self.health = (self.health + amount).min(self.config.max_health);
+        true
+    }
+
+    // ANCHOR: inventory
+    pub fn has_item(&self, item: ItemId) -> bool {
+        self.items.contains(&item)
+    }
+
+    // Returns `true` if the item was added. Every item could be carried only once.
+    pub fn add_item(&mut self, item: ItemId) -> bool {
+        if self.has_item(item) {
+            return false;
+        }
+        self.items.push(item);
+        true
+    }
+
+    pub fn items(&self) -> &[ItemId] {
+        &self.items
+    }
+    // ANCHOR_END: inventory
+
+    pub fn add_reserve_ammo(&mut self, amount: u32) {
+        self.reserve_ammo = self.reserve_ammo.saturating_add(amount);
+    }
+    // ANCHOR_END: pickup_effects
+
+    // ANCHOR: weapon_switching
+    pub fn current_weapon(&self) -> Option<&WeaponSlot> {
+        self.weapons.get(self.selected_weapon)
+    }
+
+    fn switch_weapon(&mut self, ctx: &mut ScriptContext, index: usize) {
+        if index >= self.weapons.len() || index == self.selected_weapon {
+            return;
+        }
+
+        // Switching interrupts a reload, the magazine of the previous weapon stays as it was.
+        self.reload_timer = 0.0;
+        self.selected_weapon = index;
+        self.update_weapon_models(&mut ctx.scene.graph);
+    }
+
+    // Shows the model of the selected weapon and hides the rest of them.
+    fn update_weapon_models(&self, graph: &mut Graph) {
+        for (index, weapon) in self.weapons.iter().enumerate() {
+            if let Some(model) = graph.try_get_mut(weapon.model) {
+                let is_selected =
+                    index == self.selected_weapon && !self.third_person && !self.spectating;
+                model.set_enabled(is_selected);
+                if !is_selected {
+                    model
+                        .local_transform_mut()
+                        .set_position(weapon.rest_position);
+                }
+            }
+        }
+    }
+    // ANCHOR_END: weapon_switching
+
+    pub fn crosshair_texture(&self) -> Option<&TextureResource> {
+        self.crosshair_texture.as_ref()
+    }
+
+    // A prompt of an interactable object the player looks at.
+    pub fn interaction_prompt(&self) -> Option<&str> {
+        self.interaction_prompt.as_deref()
+    }
+
+    // ANCHOR: death_cam
+    fn is_dying(&self) -> bool {
+        self.death_timer.is_some()
+    }
+
+    fn start_death_cam(&mut self, ctx: &mut ScriptContext) {
+        self.death_timer = Some(self.config.death_cam_duration);
+        self.death_cam_angle = self.smooth_yaw;
+        self.reset_input();
+        self.grapple_anchor = None;
+
+        // The weapon would hang in the air in front of the orbiting camera, so it is hidden. The
+        // character model is shown instead, so there's something to look at.
+        for weapon in self.weapons.iter() {
+            if let Some(model) = ctx.scene.graph.try_get_mut(weapon.model) {
+                model.set_enabled(false);
+            }
+        }
+        if let Some(model) = ctx.scene.graph.try_get_mut(*self.model) {
+            model.set_enabled(true);
+        }
+        if let Some(ragdoll) = ctx
+            .scene
+            .graph
+            .try_get_mut_of_type::<Ragdoll>(*self.ragdoll)
+        {
+            ragdoll.set_active(true);
+        }
+    }
+
+    // Returns `true` while the death camera is active, the player respawns after that.
+    fn update_death_cam(&mut self, ctx: &mut ScriptContext) -> bool {
+        let Some(timer) = self.death_timer else {
+            self.start_death_cam(ctx);
+            return true;
+        };
+        if timer <= 0.0 {
+            return false;
+        }
+        self.death_timer = Some(timer - ctx.dt);
+
+        // The body still falls, but it does not slide anymore.
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            let velocity = rigid_body.lin_vel();
+            rigid_body.set_lin_vel(Vector3::new(0.0, velocity.y, 0.0));
+        }
+
+        // Slowly orbit around the body, looking at it from above.
+        self.death_cam_angle += DEATH_CAM_ORBIT_SPEED * ctx.dt;
+        let angle = self.death_cam_angle.to_radians();
+        let offset = Vector3::new(
+            angle.sin() * DEATH_CAM_DISTANCE,
+            DEATH_CAM_HEIGHT,
+            angle.cos() * DEATH_CAM_DISTANCE,
+        );
+        if let Some(camera) = ctx.scene.graph.try_get_mut(self.camera) {
+            let transform = camera.local_transform_mut();
+            transform.set_position(offset);
+            transform.set_rotation(UnitQuaternion::face_towards(&-offset, &Vector3::y()));
+        }
+
+        true
+    }
+    // ANCHOR_END: death_cam
+
+    // ANCHOR: spectate
+    // Enemies and other players that are alive, in the order of the graph, so the order does not
+    // change when cycling through them.
+    fn spectate_targets(&self, ctx: &ScriptContext) -> Vec<Handle<Node>> {
+        let graph = &ctx.scene.graph;
+        graph
+            .pair_iter()
+            .filter(|(handle, node)| {
+                if node.has_script::<Enemy>() {
+                    Enemy::is_alive(graph, *handle)
+                } else if let Some(player) = node.try_get_script::<Player>() {
+                    *handle != ctx.handle && player.health > 0.0
+                } else {
+                    false
+                }
+            })
+            .map(|(handle, _)| handle)
+            .collect()
+    }
+
+    fn toggle_spectator(&mut self, ctx: &mut ScriptContext) {
+        self.spectating = !self.spectating;
+        if self.spectating {
+            // The camera starts from where it is now and flies to the first target.
+            if let Some(camera) = ctx.scene.graph.try_get(self.camera) {
+                self.spectate_position = camera.global_position();
+            }
+            self.spectate_target = Handle::NONE;
+            self.spectate_index = 0;
+            self.cycle_spectate_target(ctx, 0);
+        }
+        self.update_weapon_models(&mut ctx.scene.graph);
+    }
+
+    // Follows the target that is `step` places after the current one in the list, a negative step
+    // goes back. If the current target is gone, the one that took its place is followed.
+    fn cycle_spectate_target(&mut self, ctx: &ScriptContext, step: isize) {
+        let targets = self.spectate_targets(ctx);
+        if targets.is_empty() {
+            self.spectate_target = Handle::NONE;
+            return;
+        }
+
+        let index = match targets
+            .iter()
+            .position(|target| *target == self.spectate_target)
+        {
+            Some(index) => (index as isize + step).rem_euclid(targets.len() as isize) as usize,
+            None => self.spectate_index % targets.len(),
+        };
+        self.spectate_index = index;
+        self.spectate_target = targets[index];
+    }
+
+    // The camera orbits around the target, looking around turns it around the target. Without a
+    // target, the camera flies just like in the noclip mode. The real time step is used, so the
+    // camera is not slowed down by the time scale.
+    fn spectate(&mut self, ctx: &mut ScriptContext, real_dt: f32) {
+        // Dead targets and the ones that are removed from the scene are replaced right away.
+        self.cycle_spectate_target(ctx, 0);
+        self.update_look_smoothing(real_dt);
+
+        let yaw = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.smooth_yaw.to_radians());
+        let rotation = UnitQuaternion::from_axis_angle(
+            &UnitVector3::new_normalize(yaw * Vector3::x()),
+            self.smooth_pitch.to_radians(),
+        ) * yaw;
+        let look_vector = rotation * Vector3::z();
+        let side_vector = rotation * Vector3::x();
+
+        match ctx.scene.graph.try_get(self.spectate_target) {
+            Some(target) => {
+                let pivot = target.global_position() + Vector3::new(0.0, SPECTATE_HEIGHT, 0.0);
+                let desired_position = pivot - look_vector.scale(SPECTATE_DISTANCE);
+                let t = 1.0 - (-SPECTATE_SMOOTHING * real_dt).exp();
+                self.spectate_position += (desired_position - self.spectate_position).scale(t);
+            }
+            None => {
+                let mut direction = calculate_wish_dir(self.move_axis, look_vector, side_vector);
+                if self.jump {
+                    direction.y += 1.0;
+                }
+                if self.crouch {
+                    direction.y -= 1.0;
+                }
+                self.spectate_position += direction.scale(self.config.noclip_speed * real_dt);
+            }
+        }
+
+        // The camera is attached to the body, which does not rotate, so only its position is
+        // subtracted.
+        let body_position = ctx.scene.graph[ctx.handle].global_position();
+        if let Some(camera) = ctx.scene.graph.try_get_mut(self.camera) {
+            let transform = camera.local_transform_mut();
+            transform.set_position(self.spectate_position - body_position);
+            transform.set_rotation(rotation);
+        }
+
+        // The body stands still, but it still could fall.
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            let velocity = rigid_body.lin_vel();
+            rigid_body.set_lin_vel(Vector3::new(0.0, velocity.y, 0.0));
+        }
+    }
+    // ANCHOR_END: spectate
+
+    // ANCHOR: respawn
+    fn respawn(&mut self, ctx: &mut ScriptContext) {
+        let mut position = self.start_position;
+        if let Some(checkpoint) = self.last_checkpoint {
+            position = checkpoint;
+            self.yaw = self.checkpoint_yaw;
+            self.pitch = 0.0;
+        } else if let Some(spawn_point) = ctx.scene.graph.try_get(*self.spawn_point) {
+            position = spawn_point.global_position();
+
+            // Face the same direction as the spawn point.
+            let look = spawn_point.look_vector();
+            self.yaw = look.x.atan2(look.z).to_degrees();
+            self.pitch = 0.0;
+        }
+        // The camera must not turn smoothly to the new direction after respawn.
+        self.smooth_yaw = self.yaw;
+        self.smooth_pitch = self.pitch;
+        self.recoil_offset = Default::default();
+        self.trauma = 0.0;
+        self.mantle_target = None;
+        self.reset_fall();
+
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            rigid_body.local_transform_mut().set_position(position);
+            rigid_body.set_lin_vel(Default::default());
+            rigid_body.set_ang_vel(Default::default());
+        }
+
+        self.health = self.config.max_health;
+        self.stamina = self.config.max_stamina;
+        self.damage_indicators.clear();
+
+        // Bring the player back to life after the death camera.
+        self.death_timer = None;
+        if let Some(ragdoll) = ctx
+            .scene
+            .graph
+            .try_get_mut_of_type::<Ragdoll>(*self.ragdoll)
+        {
+            ragdoll.set_active(false);
+        }
+        if let Some(model) = ctx.scene.graph.try_get_mut(*self.model) {
+            model.set_enabled(self.third_person);
+        }
+        self.update_weapon_models(&mut ctx.scene.graph);
+        self.exhausted = false;
+    }
+    // ANCHOR_END: respawn
+
+    // ANCHOR: ground_check
+    fn update_ground_state(&mut self, ctx: &ScriptContext) {
+        let ground = cast_ray_from_body(ctx, -Vector3::y(), self.config.ground_check_distance);
+        self.was_grounded = self.grounded;
+        self.grounded = ground.is_some();
+        self.ground_normal = ground.as_ref().map_or(Vector3::y(), |ground| ground.normal);
+        self.ground_collider = ground.map_or(Handle::NONE, |ground| ground.collider);
+    }
+
+    // Returns true if the player stands on a surface that is not too steep to walk on.
+    fn is_on_walkable_ground(&self) -> bool {
+        self.grounded && self.ground_normal.y >= self.config.max_walkable_slope.to_radians().cos()
+    }
+    // ANCHOR_END: ground_check
+
+    // ANCHOR: moving_platform
+    // Moves the player together with a kinematic body it stands on. Only the position is changed,
+    // not the velocity, so the player leaves the platform with its own velocity.
+    fn ride_platform(&mut self, ctx: &mut ScriptContext) {
+        let graph = &ctx.scene.graph;
+        let platform = graph
+            .try_get(self.ground_collider)
+            .map(|collider| collider.parent())
+            .filter(|body| {
+                graph
+                    .try_get_of_type::<RigidBody>(*body)
+                    .map_or(false, |body| body.body_type() != RigidBodyType::Dynamic)
+            })
+            .unwrap_or_default();
+        let Some(position) = graph.try_get(platform).map(|body| body.global_position()) else {
+            self.platform = Handle::NONE;
+            return;
+        };
+
+        // The offset is applied only if the player stood on the same platform at the previous
+        // frame, otherwise stepping on a platform would teleport the player.
+        if platform == self.platform {
+            let delta = position - self.platform_position;
+            ctx.scene.graph[ctx.handle]
+                .local_transform_mut()
+                .offset(delta);
+        }
+
+        self.platform = platform;
+        self.platform_position = position;
+    }
+    // ANCHOR_END: moving_platform
+
+    // ANCHOR: contacts
+    fn update_contacts(&mut self, ctx: &mut ScriptContext) {
+        let graph = &ctx.scene.graph;
+        let Some(collider) = graph.try_get_of_type::<Collider>(self.collider) else {
+            return;
+        };
+        let touching = collider
+            .contacts(&graph.physics)
+            .filter(|contact| contact.has_any_active_contact)
+            .map(|contact| {
+                if contact.collider1 == self.collider {
+                    contact.collider2
+                } else {
+                    contact.collider1
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let new_contacts = touching
+            .iter()
+            .filter(|other| !self.contacts.contains(other))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.contacts = touching;
+
+        for other in new_contacts {
+            self.on_contact(other, ctx);
+        }
+    }
+
+    // Called once when the player starts touching another collider, this is the place to react
+    // to collisions.
+    fn on_contact(&mut self, other: Handle<Node>, ctx: &mut ScriptContext) {
+        if let Some(hazard) = find_hazard(&ctx.scene.graph, other) {
+            let damage = hazard.damage();
+            let position = ctx.scene.graph[other].global_position();
+            self.take_damage(damage, Some(position));
+        }
+
+        // A dash stops when the player hits something on its way, the ground does not count.
+        if self.is_dashing() && other != self.ground_collider {
+            self.dash_timer = 0.0;
+        }
+    }
+    // ANCHOR_END: contacts
+
+    // ANCHOR: is_sprinting
+    fn is_sprinting(&self) -> bool {
+        self.sprint
+            && !self.exhausted
+            && (!self.config.sprint_forward_only || self.move_axis.y > 0.0)
+    }
+    // ANCHOR_END: is_sprinting
+
+    // ANCHOR: stamina
+    pub fn stamina(&self) -> f32 {
+        self.stamina
+    }
+
+    fn update_stamina(&mut self, dt: f32) {
+        let is_moving = self.wish_dir.norm() > f32::EPSILON;
+        if self.is_sprinting() && is_moving {
+            self.stamina = (self.stamina - self.config.stamina_drain * dt).max(0.0);
+        } else if !self.config.strict_stamina_regen || self.grounded {
+            self.stamina =
+                (self.stamina + self.config.stamina_regen * dt).min(self.config.max_stamina);
+        }
+
+        if self.stamina <= 0.0 {
+            self.exhausted = true;
+        } else if self.exhausted && self.stamina >= self.config.stamina_recovery_threshold {
+            self.exhausted = false;
+        }
+    }
+    // ANCHOR_END: stamina
+
+    // ANCHOR: movement_speed
+    // The speed is the target velocity, so it must not be multiplied by the time step, otherwise
+    // the player would walk slower at higher frame rates.
+    fn movement_speed(&self) -> f32 {
+        let mut movement_speed = self.config.walk_speed;
+        if self.is_sprinting() {
+            movement_speed *= self.config.sprint_multiplier;
+        }
+        if self.crouch_factor > 0.0 {
+            movement_speed *= self.config.crouch_multiplier;
+        }
+        movement_speed
+    }
+    // ANCHOR_END: movement_speed
+
+    // ANCHOR: update_axes
+    fn update_axes(&mut self, ctx: &mut ScriptContext, real_dt: f32) {
+        let gamepad = &ctx.plugins.get::<Game>().gamepad.state;
+
+        let mut keyboard_axis = Vector2::default();
+        if self.move_forward {
+            keyboard_axis.y += 1.0;
+        }
+        if self.move_backward {
+            keyboard_axis.y -= 1.0;
+        }
+        if self.move_left {
+            keyboard_axis.x -= 1.0;
+        }
+        if self.move_right {
+            keyboard_axis.x += 1.0;
+        }
+
+        self.move_axis = keyboard_axis + gamepad.left_stick;
+        self.look_axis = gamepad.right_stick;
+
+        // Gamepad sticks give rotation speed, not offset like the mouse does.
+        let look_step = self.config.gamepad_look_speed * self.look_sensitivity_scale() * real_dt;
+        let look_y = if self.config.invert_y {
+            -self.look_axis.y
+        } else {
+            self.look_axis.y
+        };
+        self.pitch = (self.pitch - look_y * look_step).clamp(-89.9, 89.9);
+        self.yaw -= self.look_axis.x * look_step;
+    }
+    // ANCHOR_END: update_axes
+
+    // ANCHOR: fov
+    fn target_fov(&self) -> f32 {
+        let is_moving = self.wish_dir.norm() > f32::EPSILON;
+        let fov = if self.aim {
+            self.config.ads_fov
+        } else if self.is_sprinting() && is_moving {
+            self.config.fov + self.config.sprint_fov_bonus
+        } else {
+            self.config.fov
+        };
+        fov.clamp(MIN_FOV, MAX_FOV)
+    }
+
+    // Narrow field of view magnifies the image, so the look speed is reduced to keep the same
+    // precision. Wider field of view of sprinting does not speed it up.
+    fn look_sensitivity_scale(&self) -> f32 {
+        let fov = self.config.fov.clamp(MIN_FOV, MAX_FOV);
+        self.current_fov.min(fov) / fov
+    }
+
+    fn update_fov(&mut self, ctx: &mut ScriptContext) {
+        // Exponential smoothing makes the transition independent of the frame rate.
+        let t = 1.0 - (-self.config.ads_speed * ctx.dt).exp();
+        self.current_fov += (self.target_fov() - self.current_fov) * t;
+        self.current_fov = self.current_fov.clamp(MIN_FOV, MAX_FOV);
+
+        // The weapon model is moved at the same rate, so it reaches the sights when the zoom
+        // ends.
+        let target_ads = if self.aim { 1.0 } else { 0.0 };
+        self.ads_factor += (target_ads - self.ads_factor) * t;
+
+        if let Some(camera) = ctx.scene.graph.try_get_mut_of_type::<Camera>(self.camera) {
+            let mut projection = camera.projection().clone();
+            if let Projection::Perspective(ref mut perspective) = projection {
+                perspective.fov = self.current_fov.to_radians();
+            }
+            camera.set_projection(projection);
+        }
+    }
+    // ANCHOR_END: fov
+
+    // ANCHOR: look_smoothing
+    fn update_look_smoothing(&mut self, dt: f32) {
+        if self.config.look_smoothing <= 0.0 {
+            self.smooth_yaw = self.yaw;
+            self.smooth_pitch = self.pitch;
+            return;
+        }
+
+        let t = 1.0 - (-dt / self.config.look_smoothing).exp();
+        self.smooth_yaw += (self.yaw - self.smooth_yaw) * t;
+        self.smooth_pitch += (self.pitch - self.smooth_pitch) * t;
+    }
+    // ANCHOR_END: look_smoothing
+
+    // ANCHOR: weapon_sway
+    fn update_weapon_sway(&mut self, ctx: &mut ScriptContext, side_vector: Vector3<f32>) {
+        let yaw_delta = self.smooth_yaw - self.sway_prev_yaw;
+        let pitch_delta = self.smooth_pitch - self.sway_prev_pitch;
+        self.sway_prev_yaw = self.smooth_yaw;
+        self.sway_prev_pitch = self.smooth_pitch;
+
+        let Some(weapon) = self.current_weapon() else {
+            return;
+        };
+        if !ctx.scene.graph.is_valid_handle(weapon.model) || ctx.dt <= 0.0 {
+            return;
+        }
+
+        let strafe_speed = ctx
+            .scene
+            .graph
+            .try_get_of_type::<RigidBody>(ctx.handle)
+            .map_or(0.0, |rigid_body| rigid_body.lin_vel().dot(&side_vector));
+
+        // The weapon lags behind the motion, so it is offset opposite to it. The local X axis
+        // of the camera points left, positive yaw turns left and positive pitch looks down.
+        let yaw_rate = (yaw_delta / ctx.dt / SWAY_MAX_LOOK_RATE).clamp(-1.0, 1.0);
+        let pitch_rate = (pitch_delta / ctx.dt / SWAY_MAX_LOOK_RATE).clamp(-1.0, 1.0);
+        let strafe = (strafe_speed / SWAY_MAX_STRAFE_SPEED).clamp(-1.0, 1.0);
+        let target_offset = Vector3::new(-(yaw_rate + strafe).clamp(-1.0, 1.0), pitch_rate, 0.0)
+            .scale(self.config.sway_amount);
+
+        // When there's no motion, the target offset is zero and the weapon springs back to rest.
+        let t = 1.0 - (-self.config.sway_smoothing * ctx.dt).exp();
+        self.sway_offset += (target_offset - self.sway_offset).scale(t);
+
+        self.apply_weapon_offset(ctx);
+    }
+
+    fn apply_weapon_offset(&self, ctx: &mut ScriptContext) {
+        let Some(weapon) = self.current_weapon() else {
+            return;
+        };
+        let base_position = match weapon.ads_position {
+            Some(ads_position) => weapon.rest_position.lerp(&ads_position, self.ads_factor),
+            None => weapon.rest_position,
+        };
+        // Sway and bob are dampened while aiming, otherwise the sights would drift off the target.
+        let sway_scale = 1.0 - (1.0 - self.config.ads_sway_scale) * self.ads_factor;
+        let offset = (self.sway_offset + self.weapon_bob_offset).scale(sway_scale);
+        if let Some(model) = ctx.scene.graph.try_get_mut(weapon.model) {
+            model
+                .local_transform_mut()
+                .set_position(base_position + offset);
+        }
+    }
+    // ANCHOR_END: weapon_sway
+
+    // ANCHOR: camera_position
+    fn update_camera_position(&mut self, ctx: &mut ScriptContext) {
+        let eye_height = self.stand_camera_height
+            + (self.config.crouch_camera_height - self.stand_camera_height) * self.crouch_factor
+            + self.bob_offset
+            + self.landing_dip;
+
+        let Some(camera) = ctx.scene.graph.try_get(self.camera) else {
+            return;
+        };
+        let mut position = self.first_person_camera_position;
+        position.y = eye_height;
+
+        if self.third_person {
+            // The camera orbits around the head of the player, it looks at the same direction as
+            // in the first-person mode, but stays behind.
+            let look_vector = **camera.local_transform().rotation() * Vector3::z();
+            let pivot =
+                ctx.scene.graph[ctx.handle].global_position() + Vector3::y().scale(eye_height);
+
+            // Pull the camera closer when something is between it and the player.
+            let distance =
+                cast_ray_ignoring_player(ctx, pivot, -look_vector, self.config.camera_distance)
+                    .map_or(self.config.camera_distance, |intersection| {
+                        ((intersection.position.coords - pivot).norm() - CAMERA_COLLISION_MARGIN)
+                            .max(0.0)
+                    });
+            position = Vector3::new(0.0, eye_height, 0.0) - look_vector.scale(distance);
+        }
+
+        ctx.scene.graph[self.camera]
+            .local_transform_mut()
+            .set_position(position);
+    }
+
+    fn toggle_camera_mode(&mut self, ctx: &mut ScriptContext) {
+        self.third_person = !self.third_person;
+        // The model starts facing the same direction as the camera.
+        self.model_yaw = self.smooth_yaw;
+        if let Some(model) = ctx.scene.graph.try_get_mut(*self.model) {
+            model.set_enabled(self.third_person);
+        }
+        // The weapon is held in front of the camera, so it is not shown in the third-person mode.
+        self.update_weapon_models(&mut ctx.scene.graph);
+    }
+
+    // Turns the character model to where the player moves, or to where it looks when aiming or
+    // shooting. When there's no movement, the model keeps its last direction.
+    fn update_character_model(&mut self, ctx: &mut ScriptContext) {
+        if !self.third_person {
+            return;
+        }
+
+        let target_yaw = if self.aim || self.fire_held {
+            Some(self.smooth_yaw)
+        } else if self.wish_dir.xz().norm() > f32::EPSILON {
+            Some(self.wish_dir.x.atan2(self.wish_dir.z).to_degrees())
+        } else {
+            None
+        };
+
+        if let Some(target_yaw) = target_yaw {
+            // Turn by the shortest arc, otherwise the model could spin around when going from
+            // -179 to 179 degrees.
+            let delta = (target_yaw - self.model_yaw + 180.0).rem_euclid(360.0) - 180.0;
+            let t = if self.config.model_rotation_smoothing > 0.0 {
+                1.0 - (-ctx.dt / self.config.model_rotation_smoothing).exp()
+            } else {
+                1.0
+            };
+            self.model_yaw += delta * t;
+        }
+
+        if let Some(model) = ctx.scene.graph.try_get_mut(*self.model) {
+            model
+                .local_transform_mut()
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::y_axis(),
+                    self.model_yaw.to_radians(),
+                ));
+        }
+    }
+    // ANCHOR_END: camera_position
+
+    // ANCHOR: noclip
+    fn toggle_noclip(&mut self, ctx: &mut ScriptContext) {
+        self.noclip = !self.noclip;
+
+        // Kinematic bodies are moved only by changing their position and they are not pushed by
+        // other bodies, so the player passes through everything. The physics picks up the current
+        // position of the body when it becomes dynamic again.
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            rigid_body.set_body_type(if self.noclip {
+                RigidBodyType::KinematicPositionBased
+            } else {
+                RigidBodyType::Dynamic
+            });
+            rigid_body.set_lin_vel(Default::default());
+        }
+
+        // Leaving noclip high in the air must not be treated as a fall that started long ago.
+        self.reset_fall();
+    }
+
+    fn fly(
+        &mut self,
+        ctx: &mut ScriptContext,
+        look_vector: Vector3<f32>,
+        side_vector: Vector3<f32>,
+    ) {
+        let mut direction = calculate_wish_dir(self.move_axis, look_vector, side_vector);
+        if self.jump {
+            direction.y += 1.0;
+        }
+        if self.crouch {
+            direction.y -= 1.0;
+        }
+
+        let offset = direction.scale(self.config.noclip_speed * ctx.dt);
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            rigid_body.local_transform_mut().offset(offset);
+        }
+    }
+    // ANCHOR_END: noclip
+
+    // ANCHOR: swim
+    fn swim(&mut self, ctx: &mut ScriptContext) {
+        // The player swims where it looks, and the jump key makes it swim up.
+        let mut target_velocity = self.wish_dir.scale(self.config.swim_speed);
+        if self.jump {
+            target_velocity.y += self.config.swim_speed;
+        }
+
+        let is_moving = target_velocity.norm() > f32::EPSILON;
+        let rate = if is_moving {
+            self.config.acceleration
+        } else {
+            self.config.friction
+        };
+        let t = 1.0 - (-rate * ctx.dt).exp();
+
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            rigid_body.set_gravity_scale(WATER_GRAVITY_SCALE);
+
+            let current_velocity = rigid_body.lin_vel();
+            let mut velocity = current_velocity + (target_velocity - current_velocity).scale(t);
+            velocity.y += self.config.buoyancy * ctx.dt;
+            rigid_body.set_lin_vel(velocity);
+        }
+
+        // Water softens any fall.
+        self.reset_fall();
+    }
+    // ANCHOR_END: swim
+
+    // ANCHOR: climb
+    fn climb(&mut self, ctx: &mut ScriptContext, side_vector: Vector3<f32>) {
+        // Forward key moves the player up the ladder, or down if the player looks down.
+        let direction = if self.pitch > LADDER_DESCEND_PITCH {
+            -1.0
+        } else {
+            1.0
+        };
+        self.climb_velocity = self.move_axis.y * direction * self.config.climb_speed;
+
+        // Strafing is allowed, this way the player could step off the side of the ladder.
+        let strafe = side_vector.scale(-self.move_axis.x * self.config.climb_speed);
+
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            rigid_body.set_gravity_scale(0.0);
+            rigid_body.set_lin_vel(Vector3::new(strafe.x, self.climb_velocity, strafe.z));
+        }
+
+        self.reset_fall();
+    }
+
+    // When the player leaves a ladder while climbing up, it is at the top of it. A small push
+    // forward puts the player on the ledge.
+    fn climb_off_ladder(&mut self, ctx: &mut ScriptContext, look_vector: Vector3<f32>) {
+        if self.climb_velocity > 0.0 {
+            if let Some(direction) =
+                Vector3::new(look_vector.x, 0.0, look_vector.z).try_normalize(f32::EPSILON)
+            {
+                if let Some(rigid_body) =
+                    ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle)
+                {
+                    let velocity = direction.scale(LADDER_EXIT_SPEED);
+                    rigid_body.set_lin_vel(Vector3::new(
+                        velocity.x,
+                        rigid_body.lin_vel().y,
+                        velocity.z,
+                    ));
+                }
+            }
+        }
+        self.climb_velocity = 0.0;
+    }
+    // ANCHOR_END: climb
+
+    // ANCHOR: dash
+    fn is_dashing(&self) -> bool {
+        self.dash_timer > 0.0
+    }
+
+    fn update_dash(&mut self, dt: f32, look_vector: Vector3<f32>) {
+        self.dash_timer = (self.dash_timer - dt).max(0.0);
+        self.dash_cooldown_timer = (self.dash_cooldown_timer - dt).max(0.0);
+
+        if !std::mem::take(&mut self.dash) || self.dash_cooldown_timer > 0.0 {
+            return;
+        }
+
+        // Dash in the movement direction, or forward if the player stands still. Dashing is
+        // always horizontal.
+        let wish_dir = Vector3::new(self.wish_dir.x, 0.0, self.wish_dir.z);
+        let look_dir = Vector3::new(look_vector.x, 0.0, look_vector.z);
+        if let Some(direction) = wish_dir
+            .try_normalize(f32::EPSILON)
+            .or_else(|| look_dir.try_normalize(f32::EPSILON))
+        {
+            self.dash_direction = direction;
+            self.dash_timer = self.config.dash_duration;
+            self.dash_cooldown_timer = self.config.dash_cooldown;
+        }
+    }
+    // ANCHOR_END: dash
+
+    // ANCHOR: wall_run
+    // Returns a normal of a wall next to the player, either on the left or on the right.
+    fn find_wall(&self, ctx: &ScriptContext, side_vector: Vector3<f32>) -> Option<Vector3<f32>> {
+        let side = Vector3::new(side_vector.x, 0.0, side_vector.z).try_normalize(f32::EPSILON)?;
+        [side, -side].into_iter().find_map(|direction| {
+            cast_ray_from_body(ctx, direction, WALL_RUN_CHECK_DISTANCE)
+                .map(|intersection| intersection.normal)
+                .filter(|normal| normal.y.abs() < VERTICAL_SURFACE_NORMAL_Y)
+        })
+    }
+
+    // Returns `true` if the player runs on a wall, in this case its velocity is already set.
+    fn wall_run(
+        &mut self,
+        ctx: &mut ScriptContext,
+        look_vector: Vector3<f32>,
+        side_vector: Vector3<f32>,
+    ) -> bool {
+        if self.grounded {
+            self.wall_run_timer = 0.0;
+            return false;
+        }
+
+        if self.move_axis.y <= 0.0 || self.wall_run_timer >= self.config.wall_run_duration {
+            return false;
+        }
+
+        let Some(normal) = self.find_wall(ctx, side_vector) else {
+            return false;
+        };
+
+        // Run along the wall in the direction the player looks.
+        let Some(mut direction) = normal.cross(&Vector3::y()).try_normalize(f32::EPSILON) else {
+            return false;
+        };
+        if direction.dot(&look_vector) < 0.0 {
+            direction = -direction;
+        }
+
+        self.wall_run_timer += ctx.dt;
+        self.reset_fall();
+
+        let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) else {
+            return false;
+        };
+
+        let mut velocity = direction.scale(self.config.wall_run_speed);
+        if self.jump_buffer_timer >= 0.0 {
+            // Jump off the wall, the player cannot run on walls again until it lands.
+            self.jump_buffer_timer = -1.0;
+            self.wall_run_timer = self.config.wall_run_duration;
+            velocity += normal.scale(WALL_JUMP_SPEED);
+            velocity.y = self.config.jump_vel;
+            rigid_body.set_gravity_scale(1.0);
+        } else {
+            velocity.y = rigid_body.lin_vel().y;
+            rigid_body.set_gravity_scale(WALL_RUN_GRAVITY_SCALE);
+        }
+        rigid_body.set_lin_vel(velocity);
+
+        true
+    }
+    // ANCHOR_END: wall_run
+
+    // ANCHOR: grapple
+    // Returns `true` if the player is pulled by the grappling hook, in this case its velocity is
+    // already set.
+    fn update_grapple(&mut self, ctx: &mut ScriptContext) -> bool {
+        if !self.grapple {
+            self.grapple_anchor = None;
+            return false;
+        }
+
+        let Some((camera_position, camera_look)) = ctx
+            .scene
+            .graph
+            .try_get(self.camera)
+            .map(|camera| (camera.global_position(), self.aim_vector(camera)))
+        else {
+            return false;
+        };
+
+        // The hook is thrown once, when the key is pressed.
+        if self.grapple_anchor.is_none() {
+            match cast_ray_ignoring_player(
+                ctx,
+                camera_position,
+                camera_look,
+                self.config.grapple_range,
+            ) {
+                Some(intersection) => self.grapple_anchor = Some(intersection.position.coords),
+                None => {
+                    // Missed, the key must be pressed again to make another attempt.
+                    self.grapple = false;
+                    return false;
+                }
+            }
+        }
+        let Some(anchor) = self.grapple_anchor else {
+            return false;
+        };
+
+        let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) else {
+            return false;
+        };
+
+        let offset = anchor - rigid_body.global_position();
+        if offset.norm() <= GRAPPLE_RELEASE_DISTANCE {
+            self.grapple = false;
+            self.grapple_anchor = None;
+            return false;
+        }
+
+        // Spring-like pull, that is stronger when the player is far from the anchor.
+        let velocity = rigid_body.lin_vel();
+        let acceleration = offset.scale(self.config.grapple_pull) - velocity.scale(GRAPPLE_DAMPING);
+        rigid_body.set_lin_vel(velocity + acceleration.scale(ctx.dt));
+        // Standing still or climbing a ladder disables the gravity, the swing must have it back.
+        rigid_body.set_gravity_scale(1.0);
+
+        self.reset_fall();
+
+        ctx.scene.drawing_context.add_line(Line {
+            begin: camera_position - Vector3::new(0.0, 0.2, 0.0),
+            end: anchor,
+            color: Color::WHITE,
+        });
+
+        true
+    }
+    // ANCHOR_END: grapple
+
+    // ANCHOR: mantle
+    fn is_mantling(&self) -> bool {
+        self.mantle_target.is_some()
+    }
+
+    // Looks for a ledge in front of the player in the air: there must be a wall in front of the
+    // body, which ends below the grab height, and enough space to stand on top of it.
+    fn find_ledge(&self, ctx: &ScriptContext, look_vector: Vector3<f32>) -> Option<Vector3<f32>> {
+        let direction =
+            Vector3::new(look_vector.x, 0.0, look_vector.z).try_normalize(f32::EPSILON)?;
+        let position = ctx.scene.graph[ctx.handle].global_position();
+        let probe_distance = self.capsule_radius + MANTLE_REACH;
+
+        let wall_hit = cast_ray_ignoring_player(ctx, position, direction, probe_distance)?;
+        if wall_hit.normal.y.abs() > VERTICAL_SURFACE_NORMAL_Y {
+            return None;
+        }
+
+        // The forward ray at the grab height must be clear, otherwise it is a wall, not a ledge.
+        let grab =
+            position + Vector3::new(0.0, self.capsule_bottom + self.config.mantle_height, 0.0);
+        if cast_ray_ignoring_player(ctx, grab, direction, probe_distance).is_some() {
+            return None;
+        }
+
+        let above_ledge = grab + direction.scale(probe_distance);
+        let top_hit =
+            cast_ray_ignoring_player(ctx, above_ledge, -Vector3::y(), self.config.mantle_height)?;
+        if top_hit.normal.y < HORIZONTAL_SURFACE_NORMAL_Y {
+            return None;
+        }
+
+        // The whole standing capsule must fit above the ledge.
+        let stand_height = self.stand_capsule_top - self.capsule_bottom;
+        let top = top_hit.position.coords;
+        if cast_ray_ignoring_player(
+            ctx,
+            top + Vector3::new(0.0, 0.01, 0.0),
+            Vector3::y(),
+            stand_height,
+        )
+        .is_some()
+        {
+            return None;
+        }
+
+        Some(top + Vector3::new(0.0, 0.01 - self.capsule_bottom, 0.0))
+    }
+
+    // Returns true while the player climbs onto a ledge, the movement is controlled by it then.
+    fn update_mantle(&mut self, ctx: &mut ScriptContext, look_vector: Vector3<f32>) -> bool {
+        if self.mantle_target.is_none() {
+            let wants_mantle = self.move_axis.y > 0.0 || self.jump;
+            if self.grounded || !wants_mantle || self.config.mantle_height <= 0.0 {
+                return false;
+            }
+            let Some(target) = self.find_ledge(ctx, look_vector) else {
+                return false;
+            };
+            self.mantle_target = Some(target);
+            self.mantle_start = ctx.scene.graph[ctx.handle].global_position();
+            self.mantle_elapsed = 0.0;
+        }
+        let Some(target) = self.mantle_target else {
+            return false;
+        };
+
+        self.mantle_elapsed += ctx.dt;
+        let t = (self.mantle_elapsed / self.config.mantle_time.max(f32::EPSILON)).min(1.0);
+
+        // The player is pulled up first and then forward, so the body does not go through the
+        // edge of the ledge.
+        let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+        let rise = smooth((t * 2.0).min(1.0));
+        let advance = smooth((t * 2.0 - 1.0).max(0.0));
+        let offset = target - self.mantle_start;
+        let position = self.mantle_start
+            + Vector3::new(offset.x * advance, offset.y * rise, offset.z * advance);
+
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            rigid_body.local_transform_mut().set_position(position);
+            rigid_body.set_lin_vel(Vector3::default());
+            rigid_body.set_gravity_scale(if t < 1.0 { 0.0 } else { 1.0 });
+        }
+        self.reset_fall();
+
+        if t >= 1.0 {
+            self.mantle_target = None;
+            // The jump that started the mantle must not make another jump on the ledge.
+            self.jump_buffer_timer = -1.0;
+        }
+        true
+    }
+    // ANCHOR_END: mantle
+
+    // ANCHOR: reloading
+    fn is_reloading(&self) -> bool {
+        self.reload_timer > 0.0
+    }
+
+    // There's no sense to reload a full magazine or reload without spare rounds.
+    fn can_reload(&self) -> bool {
+        self.current_weapon().map_or(false, |weapon| {
+            !self.is_reloading() && weapon.ammo < weapon.magazine_size && self.reserve_ammo > 0
+        })
+    }
+
+    fn start_reload(&mut self) {
+        if self.can_reload() {
+            self.reload_timer = self.config.reload_time.max(f32::EPSILON);
+        }
+    }
+
+    fn update_reload(&mut self, dt: f32) {
+        if !self.is_reloading() {
+            return;
+        }
+
+        self.reload_timer -= dt;
+        if self.reload_timer <= 0.0 {
+            self.reload_timer = 0.0;
+
+            // Move as many rounds from the reserve as the magazine can hold.
+            let reserve_ammo = self.reserve_ammo;
+            if let Some(weapon) = self.weapons.get_mut(self.selected_weapon) {
+                let rounds = weapon
+                    .magazine_size
+                    .saturating_sub(weapon.ammo)
+                    .min(reserve_ammo);
+                weapon.ammo += rounds;
+                self.reserve_ammo -= rounds;
+            }
+        }
+    }
+    // ANCHOR_END: reloading
+
+    // ANCHOR: recoil
+    fn add_recoil(&mut self) {
+        // Negative pitch means that the camera looks up.
+        self.recoil_offset.y -= self.config.recoil_per_shot;
+        self.recoil_offset.x +=
+            self.config.recoil_per_shot * 0.3 * thread_rng().gen_range(-1.0..1.0);
+    }
+
+    fn update_recoil(&mut self, dt: f32) {
+        // The offset decays slower than it accumulates during sustained fire, so the aim drifts
+        // upwards and then returns back to where the player aimed before shooting.
+        self.recoil_offset = self
+            .recoil_offset
+            .scale((-self.config.recoil_recovery * dt).exp());
+    }
+    // ANCHOR_END: recoil
+
+    // ANCHOR: spread
+    fn update_spread(&mut self, dt: f32) {
+        self.shot_spread = (self.shot_spread - self.config.spread_recovery * dt).max(0.0);
+
+        let mut spread = self.config.base_spread
+            + self.config.moving_spread * self.wish_dir.norm().min(1.0)
+            + self.shot_spread;
+        if !self.grounded {
+            spread += self.config.air_spread;
+        }
+
+        // Both could reduce the spread at the same time, aiming takes effect gradually.
+        let crouch_scale = 1.0 + (self.config.crouch_spread_scale - 1.0) * self.crouch_factor;
+        let ads_scale = 1.0 + (self.config.ads_spread_scale - 1.0) * self.ads_factor;
+        self.spread = spread * crouch_scale * ads_scale;
+    }
+
+    fn add_shot_spread(&mut self) {
+        self.shot_spread =
+            (self.shot_spread + self.config.shot_spread).min(self.config.max_shot_spread);
+    }
+
+    // Radius of the spread cone on the screen, relative to the half of the screen height. The HUD
+    // uses it to size the crosshair.
+    pub fn spread_on_screen(&self) -> f32 {
+        let half_fov = (self.current_fov * 0.5).to_radians();
+        (self.spread * 0.5).to_radians().tan() / half_fov.tan()
+    }
+    // ANCHOR_END: spread
+
+    // ANCHOR: screen_shake
+    // Adds trauma (from 0 to 1) that shakes the camera, several sources add up.
+    pub fn add_shake(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma.max(0.0)).min(1.0);
+    }
+
+    fn update_shake(&mut self, dt: f32) {
+        self.trauma = (self.trauma - self.config.shake_decay * dt).max(0.0);
+        self.shake_time += dt;
+
+        // The squared trauma makes small shakes subtle and big ones strong.
+        let magnitude = (self.trauma * self.trauma * self.config.shake_max_angle).to_radians();
+        let time = self.shake_time * self.config.shake_frequency;
+        self.shake_rotation = UnitQuaternion::from_euler_angles(
+            shake_noise(time, 0.0) * magnitude,
+            shake_noise(time, 10.0) * magnitude,
+            shake_noise(time, 20.0) * magnitude * 0.5,
+        );
+    }
+
+    // Direction of the aim, it is the look direction of the camera without the shake.
+    fn aim_vector(&self, camera: &Node) -> Vector3<f32> {
+        (camera.global_rotation() * self.shake_rotation.inverse()) * Vector3::z()
+    }
+    // ANCHOR_END: screen_shake
+
+    // ANCHOR: camera_tilt
+    fn update_camera_tilt(&mut self, ctx: &mut ScriptContext) {
+        let yaw = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.smooth_yaw.to_radians());
+        let velocity = ctx
+            .scene
+            .graph
+            .try_get_of_type::<RigidBody>(ctx.handle)
+            .map_or(Vector3::default(), |rigid_body| rigid_body.lin_vel());
+        let strafe = (velocity.dot(&(yaw * Vector3::x())) / SWAY_MAX_STRAFE_SPEED).clamp(-1.0, 1.0);
+
+        // The local X axis points left, so strafing right rolls the camera clockwise, which is
+        // the positive angle around the look axis.
+        let roll = -strafe * self.config.strafe_tilt;
+        let is_moving = self.wish_dir.norm() > f32::EPSILON;
+        // Narrow field of view magnifies the tilt, so it is reduced the same way as the look
+        // speed.
+        let forward = if self.is_sprinting() && is_moving && self.grounded {
+            self.config.sprint_tilt * self.look_sensitivity_scale()
+        } else {
+            0.0
+        };
+
+        let t = 1.0 - (-self.config.tilt_smoothing * ctx.dt).exp();
+        self.camera_tilt += (Vector2::new(roll, forward) - self.camera_tilt).scale(t);
+    }
+
+    // Rotation in the local space of the camera, it is applied after the yaw and the pitch.
+    fn tilt_rotation(&self) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::z_axis(), self.camera_tilt.x.to_radians())
+            * UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.camera_tilt.y.to_radians())
+    }
+    // ANCHOR_END: camera_tilt
+
+    // ANCHOR: view_rotation
+    // The camera rotation that follows the input directly is replaced with the smoothed one, with
+    // the recoil, the tilt and the screen shake on top of it.
+    fn update_view_rotation(&self, ctx: &mut ScriptContext) {
+        let Some(camera) = ctx.scene.graph.try_get_mut(self.camera) else {
+            return;
+        };
+
+        // Recoil is applied on top of the aim, but the pitch limits must still be respected.
+        let yaw_angle = self.smooth_yaw + self.recoil_offset.x;
+        let pitch_angle = (self.smooth_pitch + self.recoil_offset.y).clamp(-89.9, 89.9);
+
+        let yaw = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw_angle.to_radians());
+        camera.local_transform_mut().set_rotation(
+            UnitQuaternion::from_axis_angle(
+                &UnitVector3::new_normalize(yaw * Vector3::x()),
+                pitch_angle.to_radians(),
+            ) * yaw
+                * self.tilt_rotation()
+                * self.shake_rotation,
+        );
+    }
+    // ANCHOR_END: view_rotation
+
+    // ANCHOR: muzzle_flash
+    fn update_muzzle_flash(&mut self, ctx: &mut ScriptContext) {
+        if self.muzzle_flash_timer > 0.0 {
+            self.muzzle_flash_timer -= ctx.dt;
+        }
+
+        // The flash is optional, shooting works fine without it.
+        if let Some(muzzle_flash) = ctx.scene.graph.try_get_mut(*self.muzzle_flash) {
+            muzzle_flash.set_enabled(self.muzzle_flash_timer > 0.0);
+        }
+    }
+
+    fn spawn_muzzle_effect(&self, ctx: &mut ScriptContext) {
+        let Some(camera) = ctx.scene.graph.try_get(self.camera) else {
+            return;
+        };
+        let direction = self.aim_vector(camera);
+        let Some(position) = self
+            .current_weapon()
+            .and_then(|weapon| ctx.scene.graph.try_get(weapon.weapon))
+            .map(|weapon| weapon.global_position())
+        else {
+            return;
+        };
+        spawn_effect(
+            ctx.scene,
+            self.muzzle_effect.as_ref(),
+            position,
+            math::vector_to_quat(direction),
+        );
+    }
+    // ANCHOR_END: muzzle_flash
+
+    // ANCHOR: hit_scan
+    fn fire(&mut self, ctx: &mut ScriptContext, damage: f32) {
+        let Some(camera) = ctx.scene.graph.try_get(self.camera) else {
+            return;
+        };
+
+        // Shots go where the player looks, but randomly deviate within the spread cone.
+        let origin = camera.global_position();
+        let direction =
+            random_direction_in_cone(self.aim_vector(camera), (self.spread * 0.5).to_radians());
+
+        // ANCHOR: penetration
+        let (max_penetrations, penetration_damage) =
+            self.current_weapon().map_or((0, 1.0), |weapon| {
+                (weapon.max_penetrations, weapon.penetration_damage)
+            });
+
+        // A piercing shot is a chain of rays, every next one starts right behind the previous hit.
+        let mut ray_origin = origin;
+        let mut range = SHOT_RANGE;
+        let mut damage = damage;
+        let mut penetrations = 0;
+        let mut hit_colliders = Vec::new();
+        let mut end = None;
+        while let Some(intersection) = cast_ray_ignoring_player(ctx, ray_origin, direction, range) {
+            let position = intersection.position.coords;
+            range -= (position - ray_origin).norm() + PENETRATION_STEP;
+            ray_origin = position + direction.scale(PENETRATION_STEP);
+
+            // The next ray could start inside of the same collider and hit its back side.
+            if !hit_colliders.contains(&intersection.collider) {
+                hit_colliders.push(intersection.collider);
+                end = Some(position);
+
+                // ANCHOR: damage_falloff_on_hit
+                let distance = (position - origin).norm();
+                let falloff = self
+                    .current_weapon()
+                    .map_or(1.0, |weapon| weapon.damage_falloff(distance));
+                // ANCHOR_END: damage_falloff_on_hit
+
+                // Only targets could be pierced, the level geometry stops the shot.
+                let is_target = self.hit(ctx, &intersection, damage * falloff);
+                if !is_target || penetrations >= max_penetrations {
+                    break;
+                }
+                penetrations += 1;
+                damage *= penetration_damage;
+            }
+
+            if range <= 0.0 {
+                break;
+            }
+        }
+        // ANCHOR_END: penetration
+
+        let Some(end) = end else {
+            return;
+        };
+
+        // Tracers start at the weapon, otherwise they would be hidden right behind the crosshair.
+        let muzzle = self
+            .current_weapon()
+            .and_then(|weapon| ctx.scene.graph.try_get(weapon.weapon))
+            .map_or(origin, |weapon| weapon.global_position());
+        self.tracers.push(Tracer {
+            begin: muzzle,
+            end,
+            frames_left: TRACER_FRAMES,
+        });
+    }
+
+    // Shows the impact of a shot and damages the thing that was hit. Returns true if the thing has
+    // health.
+    fn hit(&mut self, ctx: &mut ScriptContext, intersection: &Intersection, damage: f32) -> bool {
+        if let Some(impact_marker) = self.impact_marker.as_ref() {
+            impact_marker.instantiate_at(
+                ctx.scene,
+                intersection.position.coords,
+                math::vector_to_quat(intersection.normal),
+            );
+        }
+
+        self.place_decal(ctx, intersection.position.coords, intersection.normal);
+
+        let surface = find_surface_kind(&ctx.scene.graph, intersection.collider);
+        let effect = self
+            .surface_impact_effects
+            .effect_for(surface, self.impact_effect.as_ref());
+        spawn_effect(
+            ctx.scene,
+            effect,
+            intersection.position.coords,
+            math::vector_to_quat(intersection.normal),
+        );
+
+        let sounds = self
+            .surface_impact_sounds
+            .sounds_for(surface, &self.impact_sounds);
+        if let Some(buffer) = sounds.choose(&mut thread_rng()) {
+            play_sound(
+                &mut ctx.scene.graph,
+                buffer,
+                intersection.position.coords,
+                1.0,
+            );
+        }
+
+        let Some(health) = find_health_mut(&mut ctx.scene.graph, intersection.collider) else {
+            return false;
+        };
+        // Things that are already dead cannot be hit anymore.
+        if !health.is_dead() {
+            health.damage(damage);
+            let killed = health.is_dead();
+            self.register_hit(intersection.position.coords, damage, killed);
+        }
+        true
+    }
+    // ANCHOR_END: hit_scan
+
+    // ANCHOR: hit_marker
+    fn register_hit(&mut self, position: Vector3<f32>, damage: f32, killed: bool) {
+        self.hit_marker_timer = HIT_MARKER_DURATION;
+        self.hit_marker_kill = killed;
+        if self.config.show_damage_numbers {
+            self.damage_numbers.push(DamageNumber {
+                position,
+                amount: damage,
+                time_left: DAMAGE_NUMBER_DURATION,
+            });
+        }
+    }
+
+    fn update_hit_marker(&mut self, dt: f32) {
+        self.hit_marker_timer = (self.hit_marker_timer - dt).max(0.0);
+        for damage_number in self.damage_numbers.iter_mut() {
+            damage_number.time_left -= dt;
+        }
+        self.damage_numbers
+            .retain(|damage_number| damage_number.time_left > 0.0);
+    }
+
+    // Returns the opacity of the hit marker and whether the last hit was a kill.
+    pub fn hit_marker(&self) -> Option<(f32, bool)> {
+        (self.hit_marker_timer > 0.0).then_some((
+            self.hit_marker_timer / HIT_MARKER_DURATION,
+            self.hit_marker_kill,
+        ))
+    }
+
+    // Returns world positions and amounts of recent damage, along with their progress from 0 to 1.
+    pub fn damage_numbers(&self) -> impl Iterator<Item = (Vector3<f32>, f32, f32)> + '_ {
+        self.damage_numbers.iter().map(|damage_number| {
+            (
+                damage_number.position,
+                damage_number.amount,
+                1.0 - damage_number.time_left / DAMAGE_NUMBER_DURATION,
+            )
+        })
+    }
+
+    pub fn camera(&self) -> Handle<Node> {
+        self.camera
+    }
+
+    // Direction (in degrees) that the camera faces around the vertical axis.
+    pub fn yaw(&self) -> f32 {
+        self.smooth_yaw
+    }
+    // ANCHOR_END: hit_marker
+
+    // ANCHOR: decals
+    fn place_decal(
+        &mut self,
+        ctx: &mut ScriptContext,
+        position: Vector3<f32>,
+        normal: Vector3<f32>,
+    ) {
+        let Some(decal_prefab) = self.decal_prefab.as_ref() else {
+            return;
+        };
+        let rotation = math::vector_to_quat(normal);
+
+        // Create new decals until the limit is reached, after that reuse the oldest ones. Decals
+        // could be removed by something else, such ones are replaced with new instances.
+        let reused = self
+            .decals
+            .get(self.next_decal)
+            .filter(|decal| ctx.scene.graph.is_valid_handle(**decal))
+            .cloned();
+        if let Some(decal) = reused {
+            let transform = ctx.scene.graph[decal].local_transform_mut();
+            transform.set_position(position);
+            transform.set_rotation(rotation);
+        } else {
+            let decal = decal_prefab.instantiate_at(ctx.scene, position, rotation);
+            if self.next_decal < self.decals.len() {
+                self.decals[self.next_decal] = decal;
+            } else {
+                self.decals.push(decal);
+            }
+        }
+        self.next_decal = (self.next_decal + 1) % MAX_DECALS;
+    }
+
+    fn update_tracers(&mut self, ctx: &mut ScriptContext) {
+        for tracer in self.tracers.iter_mut() {
+            ctx.scene.drawing_context.add_line(Line {
+                begin: tracer.begin,
+                end: tracer.end,
+                color: Color::opaque(255, 220, 120),
+            });
+            tracer.frames_left = tracer.frames_left.saturating_sub(1);
+        }
+        self.tracers.retain(|tracer| tracer.frames_left > 0);
+    }
+    // ANCHOR_END: decals
+
+    // ANCHOR: melee
+    fn update_melee(&mut self, ctx: &mut ScriptContext) {
+        self.melee_timer -= ctx.dt;
+        if !std::mem::take(&mut self.melee) || self.melee_timer > 0.0 {
+            return;
+        }
+        self.melee_timer = self.config.melee_cooldown;
+
+        let Some(camera) = ctx.scene.graph.try_get(self.camera) else {
+            return;
+        };
+        let origin = camera.global_position();
+        let direction = self.aim_vector(camera);
+
+        if let Some(melee_sound) = self.melee_sound.as_ref() {
+            play_sound(&mut ctx.scene.graph, melee_sound, origin, 1.0);
+        }
+
+        let Some(intersection) =
+            cast_ray_ignoring_player(ctx, origin, direction, self.config.melee_range)
+        else {
+            return;
+        };
+
+        if let Some(health) = find_health_mut(&mut ctx.scene.graph, intersection.collider) {
+            health.damage(self.config.melee_damage);
+        }
+
+        // Push the target away from the player, this works only for dynamic rigid bodies.
+        let body = ctx.scene.graph[intersection.collider].parent();
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(body) {
+            rigid_body.apply_impulse(direction.scale(self.config.melee_knockback));
+        }
+    }
+    // ANCHOR_END: melee
+
+    // ANCHOR: flashlight
+    fn update_flashlight(&mut self, ctx: &mut ScriptContext) {
+        let capacity = self.config.flashlight_battery;
+        if self.flashlight_on {
+            self.battery = (self.battery - ctx.dt).max(0.0);
+            if self.battery <= 0.0 {
+                self.flashlight_on = false;
+            }
+        } else {
+            self.battery = (self.battery + self.config.flashlight_recharge * ctx.dt).min(capacity);
+        }
+
+        // A nearly empty battery makes the light flicker randomly, the lower the charge is, the
+        // more often the light goes off.
+        let mut enabled = self.flashlight_on;
+        let charge = self.battery / capacity.max(f32::EPSILON);
+        let threshold = self.config.flashlight_flicker_threshold;
+        if enabled && charge < threshold {
+            enabled = thread_rng().gen_range(0.0..threshold) < charge;
+        }
+
+        if let Some(flashlight) = ctx.scene.graph.try_get_mut(*self.flashlight) {
+            flashlight.set_enabled(enabled);
+        }
+    }
+    // ANCHOR_END: flashlight
+
+    // ANCHOR: launch_projectile
+    fn launch_projectile(&mut self, ctx: &mut ScriptContext) {
+        let Some(projectile_prefab) = self.projectile_prefab.as_ref() else {
+            return;
+        };
+        let Some(camera) = ctx.scene.graph.try_get(self.camera) else {
+            return;
+        };
+
+        let direction = self.aim_vector(camera);
+        // Spawn the projectile a bit in front of the camera, so it won't hit the player itself.
+        let position = camera.global_position() + direction;
+
+        let projectile =
+            projectile_prefab.instantiate_at(ctx.scene, position, math::vector_to_quat(direction));
+
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(projectile) {
+            rigid_body.set_lin_vel(direction.scale(self.config.projectile_speed));
+        }
+    }
+    // ANCHOR_END: launch_projectile
+
+    // ANCHOR: grenade
+    fn update_grenade(&mut self, ctx: &mut ScriptContext) {
+        if self.grenade {
+            if self.grenade_cook.is_none()
+                && self.grenade_count > 0
+                && self.grenade_prefab.is_some()
+            {
+                self.grenade_cook = Some(0.0);
+            }
+            if let Some(cook) = self.grenade_cook.as_mut() {
+                *cook += ctx.dt;
+            }
+        } else if let Some(cook) = self.grenade_cook.take() {
+            self.throw_grenade(ctx, cook);
+        }
+    }
+
+    fn throw_grenade(&mut self, ctx: &mut ScriptContext, cook: f32) {
+        let Some(grenade_prefab) = self.grenade_prefab.as_ref() else {
+            return;
+        };
+        let Some(camera) = ctx.scene.graph.try_get(self.camera) else {
+            return;
+        };
+
+        let direction = self.aim_vector(camera);
+        // Just like projectiles, grenades appear in front of the camera to not hit the player.
+        let position = camera.global_position() + direction;
+        let velocity = direction.scale(self.config.grenade_throw_speed)
+            + Vector3::y().scale(self.config.grenade_toss_speed);
+
+        let grenade =
+            grenade_prefab.instantiate_at(ctx.scene, position, math::vector_to_quat(direction));
+        self.grenade_count -= 1;
+
+        if let Some(script) = ctx.scene.graph[grenade].try_get_script_mut::<Grenade>() {
+            script.cook(cook);
+        }
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(grenade) {
+            rigid_body.set_lin_vel(velocity);
+        }
+    }
+    // ANCHOR_END: grenade
+
+    // ANCHOR: crouching
+    fn update_crouch(&mut self, ctx: &mut ScriptContext) {
+        let mut target_factor = if self.crouch { 1.0 } else { 0.0 };
+
+        // Do not let the player stand up, if there's something above the head.
+        if !self.crouch && self.crouch_factor > 0.0 {
+            if cast_ray_from_body(ctx, Vector3::y(), self.stand_capsule_top).is_some() {
+                target_factor = self.crouch_factor;
+            }
+        }
+
+        let step = if self.config.crouch_time > 0.0 {
+            ctx.dt / self.config.crouch_time
+        } else {
+            1.0
+        };
+        let new_factor = if target_factor > self.crouch_factor {
+            (self.crouch_factor + step).min(target_factor)
+        } else {
+            (self.crouch_factor - step).max(target_factor)
+        };
+
+        if new_factor == self.crouch_factor {
+            return;
+        }
+        self.crouch_factor = new_factor;
+
+        // Shrink the capsule from the top, this way its bottom stays on the ground.
+        if let Some(collider) = ctx
+            .scene
+            .graph
+            .try_get_mut_of_type::<Collider>(self.collider)
+        {
+            if let ColliderShape::Capsule(mut capsule) = collider.shape().clone() {
+                let scale = 1.0 + (self.config.crouch_collider_scale - 1.0) * self.crouch_factor;
+                let bottom = capsule.begin.y.min(capsule.end.y);
+                capsule.begin.y = bottom;
+                capsule.end.y = bottom + self.stand_capsule_length * scale;
+                collider.set_shape(ColliderShape::Capsule(capsule));
+            }
+        }
+    }
+    // ANCHOR_END: crouching
+
+    // ANCHOR: slide
+    // The player stays crouched during a slide, so the camera is lowered and the crouching makes
+    // sure that there's enough space above the head before standing up.
+    fn update_slide(&mut self, ctx: &mut ScriptContext) {
+        let crouch_pressed = self.crouch && !self.slide_crouch_held;
+        self.slide_crouch_held = self.crouch;
+
+        let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) else {
+            return;
+        };
+        let velocity = rigid_body.lin_vel();
+
+        if self.sliding {
+            let speed = Vector3::new(velocity.x, 0.0, velocity.z).norm();
+            if !self.crouch || !self.grounded || speed < SLIDE_MIN_SPEED {
+                self.sliding = false;
+            }
+            return;
+        }
+
+        let is_moving = self.wish_dir.norm() > f32::EPSILON;
+        if !crouch_pressed
+            || !self.is_sprinting()
+            || !is_moving
+            || !self.grounded
+            || self.config.slide_boost <= 0.0
+        {
+            return;
+        }
+
+        // The boost is added to the current velocity, so the momentum is kept.
+        if let Some(direction) =
+            Vector3::new(self.wish_dir.x, 0.0, self.wish_dir.z).try_normalize(f32::EPSILON)
+        {
+            rigid_body.set_lin_vel(velocity + direction.scale(self.config.slide_boost));
+            self.sliding = true;
+        }
+    }
+    // ANCHOR_END: slide
+
+    // ANCHOR: step_climbing
+    fn climb_steps(&mut self, ctx: &mut ScriptContext) {
+        if !self.grounded || self.config.max_step_height <= 0.0 {
+            return;
+        }
+
+        let Some(direction) =
+            Vector3::new(self.wish_dir.x, 0.0, self.wish_dir.z).try_normalize(f32::EPSILON)
+        else {
+            return;
+        };
+
+        let position = ctx.scene.graph[ctx.handle].global_position();
+        let feet = position + Vector3::new(0.0, self.capsule_bottom + 0.05, 0.0);
+        let knees = feet + Vector3::new(0.0, self.config.max_step_height, 0.0);
+        let probe_distance = self.capsule_radius + 0.1;
+
+        // There must be a vertical obstacle right in front of the feet...
+        let Some(foot_hit) = cast_ray_ignoring_player(ctx, feet, direction, probe_distance) else {
+            return;
+        };
+        if foot_hit.normal.y.abs() > VERTICAL_SURFACE_NORMAL_Y {
+            // It is a ramp, not a step.
+            return;
+        }
+
+        // ...which is low enough. If there's something at knee height, then it is a wall.
+        if cast_ray_ignoring_player(ctx, knees, direction, probe_distance).is_some() {
+            return;
+        }
+
+        // Find the top of the step and make sure that it is possible to stand on it.
+        let above_step = knees + direction.scale(probe_distance);
+        let Some(top_hit) =
+            cast_ray_ignoring_player(ctx, above_step, -Vector3::y(), self.config.max_step_height)
+        else {
+            return;
+        };
+        if top_hit.normal.y < HORIZONTAL_SURFACE_NORMAL_Y {
+            return;
+        }
+
+        let step_height = top_hit.position.y - (position.y + self.capsule_bottom);
+        if step_height <= 0.0 {
+            return;
+        }
+
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            rigid_body
+                .local_transform_mut()
+                .offset(Vector3::new(0.0, step_height + 0.01, 0.0) + direction.scale(0.05));
+        }
+    }
+    // ANCHOR_END: step_climbing
+
+    // ANCHOR: ground_snap
+    // Pulls the player down right after it has lost the ground, if there's walkable ground close
+    // enough below.
+    fn snap_to_ground(&mut self, ctx: &mut ScriptContext) {
+        if !self.was_grounded
+            || self.grounded
+            || self.ground_snap_delay > 0.0
+            || self.config.snap_distance <= 0.0
+        {
+            return;
+        }
+
+        let position = ctx.scene.graph[ctx.handle].global_position();
+        let feet = position.y + self.capsule_bottom;
+        let Some(ground) = cast_ray_from_body(
+            ctx,
+            -Vector3::y(),
+            -self.capsule_bottom + self.config.snap_distance,
+        ) else {
+            return;
+        };
+
+        // Steep surfaces must not hold the player, it should slide or fall off them.
+        if ground.normal.y < self.config.max_walkable_slope.to_radians().cos() {
+            return;
+        }
+
+        let distance = feet - ground.position.y;
+        if distance <= 0.0 || distance > self.config.snap_distance {
+            return;
+        }
+
+        if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+            // Something has thrown the player up, it's not walking down.
+            let velocity = rigid_body.lin_vel();
+            if velocity.y > 0.0 {
+                return;
+            }
+
+            rigid_body
+                .local_transform_mut()
+                .offset(Vector3::new(0.0, -distance, 0.0));
+            rigid_body.set_lin_vel(Vector3::new(velocity.x, 0.0, velocity.z));
+        }
+
+        self.grounded = true;
+    }
+    // ANCHOR_END: ground_snap
+
+    // ANCHOR: pause
+    fn toggle_pause(&mut self, ctx: &mut ScriptContext) {
+        let game = ctx.plugins.get_mut::<Game>();
+        game.paused = !game.paused;
+        let paused = game.paused;
+
+        // Keys that are held at the moment of pausing will be released while the game is paused,
+        // so the input must be reset to not leave the player running after the pause.
+        self.reset_input();
+
+        if self.config.grab_cursor {
+            set_cursor_grab(ctx, !paused);
+        }
+    }
+
+    fn reset_input(&mut self) {
+        self.move_forward = false;
+        self.move_backward = false;
+        self.move_left = false;
+        self.move_right = false;
+        self.jump = false;
+        self.shoot = false;
+        self.fire_held = false;
+        self.action_buffer.clear();
+        self.aim = false;
+        self.grapple = false;
+        // A grenade that is cooked is put back, the fuse is not burning.
+        self.grenade = false;
+        self.grenade_cook = None;
+        // Toggled states do not depend on held keys, so they are kept.
+        if !self.config.sprint_toggle {
+            self.sprint = false;
+        }
+        if !self.config.crouch_toggle {
+            self.crouch = false;
+        }
+    }
+    // ANCHOR_END: pause
+
+    // ANCHOR: interaction
+    fn update_interaction(&mut self, ctx: &mut ScriptContext) {
+        self.interaction_target = Handle::NONE;
+        self.interaction_prompt = None;
+
+        if let Some(camera) = ctx.scene.graph.try_get(self.camera) {
+            let origin = camera.global_position();
+            let direction = self.aim_vector(camera);
+            if let Some(intersection) =
+                cast_ray_ignoring_player(ctx, origin, direction, self.config.interaction_range)
+            {
+                if let Some((target, interaction)) =
+                    find_interaction(&ctx.scene.graph, intersection.collider)
+                {
+                    self.interaction_target = target;
+                    self.interaction_prompt = Some(interaction.prompt.clone());
+                }
+            }
+        }
+
+        // Interaction is a single action, and it must not repeat while the key is held.
+        if std::mem::take(&mut self.interact) && self.interaction_target.is_some() {
+            ctx.message_sender.send_to_target(
+                self.interaction_target,
+                InteractMessage { player: ctx.handle },
+            );
+        }
+    }
+    // ANCHOR_END: interaction
+
+    // ANCHOR: footsteps
+    fn is_stepping(&self, horizontal_speed: f32) -> bool {
+        self.grounded && horizontal_speed >= FOOTSTEP_SPEED_THRESHOLD
+    }
+
+    fn update_footsteps(&mut self, ctx: &mut ScriptContext, horizontal_speed: f32) {
+        if !self.is_stepping(horizontal_speed) {
+            // The next step will sound right after the player starts moving again. The step
+            // progress is kept as is, this way the weapon bob fades out from where it was.
+            self.footstep_timer = 0.0;
+            return;
+        }
+
+        // The faster the player moves, the more frequent the steps are.
+        let speed_scale = horizontal_speed / self.config.walk_speed.max(f32::EPSILON);
+
+        self.footstep_timer -= ctx.dt * speed_scale;
+        if self.footstep_timer <= 0.0 {
+            self.footstep_timer = self.config.footstep_interval;
+            self.step_side = -self.step_side;
+
+            let surface = find_surface_kind(&ctx.scene.graph, self.ground_collider);
+            let sounds = self
+                .surface_footstep_sounds
+                .sounds_for(surface, &self.footstep_sounds);
+            if let Some(buffer) = sounds.choose(&mut thread_rng()) {
+                let position = ctx.scene.graph[ctx.handle].global_position();
+                play_sound(&mut ctx.scene.graph, buffer, position, 1.0);
+            }
+        }
+
+        let interval = self.config.footstep_interval.max(f32::EPSILON);
+        self.step_progress = (1.0 - self.footstep_timer / interval).clamp(0.0, 1.0);
+    }
+    // ANCHOR_END: footsteps
+
+    // ANCHOR: reset_fall
+    // Must be called every time the player is moved by force (respawn, teleport, etc.), otherwise
+    // the speed of a fall before moving could be treated as landing speed.
+    fn reset_fall(&mut self) {
+        self.fall_speed = 0.0;
+        self.landing_dip = 0.0;
+    }
+    // ANCHOR_END: reset_fall
+
+    // ANCHOR: landing
+    fn update_landing(&mut self, ctx: &mut ScriptContext) {
+        if self.grounded && !self.was_grounded {
+            // The velocity is already reset by the physics at the moment of landing, so the
+            // speed from the previous frame is used.
+            let impact_speed = self.fall_speed;
+            if impact_speed > self.config.landing_speed_threshold {
+                if let Some(landing_sound) = self.landing_sound.as_ref() {
+                    let position = ctx.scene.graph[ctx.handle].global_position();
+                    let gain =
+                        (impact_speed / (self.config.landing_speed_threshold * 3.0)).min(1.0);
+                    play_sound(&mut ctx.scene.graph, landing_sound, position, gain);
+                }
+
+                self.landing_dip = -(impact_speed * LANDING_DIP_SCALE).min(MAX_LANDING_DIP);
+            }
+
+            if impact_speed > self.config.safe_fall_speed {
+                let excess = impact_speed / self.config.safe_fall_speed - 1.0;
+                self.add_shake(excess.clamp(0.3, 1.0));
+                self.take_damage(
+                    (impact_speed - self.config.safe_fall_speed) * self.config.fall_damage_scale,
+                    None,
+                );
+            }
+        }
+
+        // Stronger gravity gives higher landing speed for the same height, so the speed is
+        // normalized to keep the landing thresholds tied to the height of a fall.
+        let gravity_scale = self.config.gravity_scale.max(f32::EPSILON);
+        self.fall_speed = ctx
+            .scene
+            .graph
+            .try_get_of_type::<RigidBody>(ctx.handle)
+            .map_or(0.0, |rigid_body| (-rigid_body.lin_vel().y).max(0.0))
+            / gravity_scale.sqrt();
+
+        // Smoothly return the camera back after a landing.
+        self.landing_dip *= (-10.0 * ctx.dt).exp();
+    }
+    // ANCHOR_END: landing
+
+    // ANCHOR: head_bob
+    fn update_head_bob(&mut self, ctx: &mut ScriptContext, horizontal_speed: f32) {
+        let is_walking = horizontal_speed > BOB_SPEED_THRESHOLD;
+
+        // Fade the bob in when walking and out when standing still, this way the camera won't
+        // jerk when the player stops.
+        let fade_speed = 4.0 * ctx.dt;
+        if is_walking {
+            self.bob_weight = (self.bob_weight + fade_speed).min(1.0);
+            // The faster the player moves, the faster the head bobs.
+            let speed_scale = horizontal_speed / self.config.walk_speed.max(f32::EPSILON);
+            self.bob_phase +=
+                std::f32::consts::TAU * self.config.bob_frequency * speed_scale * ctx.dt;
+            self.bob_phase %= std::f32::consts::TAU;
+        } else {
+            self.bob_weight = (self.bob_weight - fade_speed).max(0.0);
+            if self.bob_weight == 0.0 {
+                self.bob_phase = 0.0;
+            }
+        }
+
+        // The offset is applied to the camera on the next update, see `update_camera_position`.
+        self.bob_offset = self.config.bob_amplitude * self.bob_weight * self.bob_phase.sin();
+    }
+    // ANCHOR_END: head_bob
+
+    // ANCHOR: weapon_bob
+    fn update_weapon_bob(&mut self, ctx: &mut ScriptContext, horizontal_speed: f32) {
+        let fade_speed = 4.0 * ctx.dt;
+        if self.is_stepping(horizontal_speed) {
+            self.weapon_bob_weight = (self.weapon_bob_weight + fade_speed).min(1.0);
+        } else {
+            self.weapon_bob_weight = (self.weapon_bob_weight - fade_speed).max(0.0);
+        }
+
+        // The bob is driven by the footsteps: the weapon is at its lowest point at the moment a
+        // step sounds, and swings to one side during a step and to the other during the next one.
+        let angle = std::f32::consts::PI * self.step_progress;
+        self.weapon_bob_offset = Vector3::new(
+            self.config.weapon_bob_horizontal * self.step_side * angle.sin(),
+            -self.config.weapon_bob_vertical * (2.0 * angle).cos(),
+            0.0,
+        )
+        .scale(self.weapon_bob_weight);
+
+        self.apply_weapon_offset(ctx);
+    }
+    // ANCHOR_END: weapon_bob
+
+    // ANCHOR: system_event
+    // Handles the events that are not the controls of the player, returns `true` if the controls
+    // must ignore the event.
+    fn handle_system_event(&mut self, event: &Event<()>, ctx: &mut ScriptContext) -> bool {
+        // ANCHOR: focus_on_os_event
+        // Release the cursor when the user switches to another window and grab it back on return.
+        if let Event::WindowEvent {
+            event: WindowEvent::Focused(focused),
+            ..
+        } = event
+        {
+            let paused = ctx.plugins.get::<Game>().paused;
+            if self.config.grab_cursor && !paused {
+                set_cursor_grab(ctx, *focused);
+            }
+        }
+        // ANCHOR_END: focus_on_os_event
+
+        // ANCHOR: pause_on_os_event
+        // Pause key works in both states, everything else is ignored while the game is paused.
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { event, .. },
+            ..
+        } = event
+        {
+            if let PhysicalKey::Code(code) = event.physical_key {
+                if event.state == ElementState::Pressed
+                    && !event.repeat
+                    && translate_key_to_ui(code) == self.key_bindings.pause
+                {
+                    self.toggle_pause(ctx);
+                    return true;
+                }
+            }
+        }
 
-    #[visit(optional)]
-    #[reflect(hidden)]
-    move_backward: bool,
+        if ctx.plugins.get::<Game>().paused {
+            return true;
+        }
+        // ANCHOR_END: pause_on_os_event
 
-    #[visit(optional)]
-    #[reflect(hidden)]
-    move_left: bool,
+        // ANCHOR: cutscene_on_os_event
+        if self.cutscene {
+            return true;
+        }
+        // ANCHOR_END: cutscene_on_os_event
 
-    #[visit(optional)]
-    #[reflect(hidden)]
-    move_right: bool,
+        // ANCHOR: death_cam_on_os_event
+        self.is_dying()
+        // ANCHOR_END: death_cam_on_os_event
+    }
+    // ANCHOR_END: system_event
 
-    #[visit(optional)]
-    #[reflect(hidden)]
-    yaw: f32,
+    // ANCHOR: action_keys
+    // Handles the keys that are not responsible for movement.
+    fn handle_action_key(
+        &mut self,
+        key: KeyCode,
+        is_pressed: bool,
+        repeat: bool,
+        ctx: &mut ScriptContext,
+    ) {
+        let bindings = self.key_bindings.clone();
+        if key == bindings.jump {
+            self.jump = is_pressed;
+            if is_pressed && !repeat {
+                self.jump_buffer_timer = self.config.jump_buffer;
+            }
+        } else if key == bindings.sprint {
+            if !self.config.sprint_toggle {
+                self.sprint = is_pressed;
+            } else if is_pressed && !repeat {
+                self.sprint = !self.sprint;
+            }
+        } else if key == bindings.crouch {
+            // When toggled off under a low ceiling, the player stays crouched until
+            // there's enough room to stand up.
+            if !self.config.crouch_toggle {
+                self.crouch = is_pressed;
+            } else if is_pressed && !repeat {
+                self.crouch = !self.crouch;
+            }
+        } else if key == bindings.reload {
+            if is_pressed && !repeat {
+                // A press that can't start a reload right now is remembered, this
+                // way it is not lost if it came a bit too early.
+                if self.can_reload() {
+                    self.start_reload();
+                } else {
+                    self.action_buffer
+                        .push(BufferedAction::Reload, ctx.elapsed_time);
+                }
+            }
+        } else if key == bindings.interact {
+            if is_pressed && !repeat {
+                self.interact = true;
+            }
+        } else if key == bindings.switch_fire_mode && is_pressed {
+            self.fire_mode = match self.fire_mode {
+                FireMode::HitScan => FireMode::Projectile,
+                FireMode::Projectile => FireMode::HitScan,
+            };
+        } else if key == bindings.noclip && is_pressed && !repeat {
+            self.toggle_noclip(ctx);
+        } else if key == bindings.dash && is_pressed && !repeat {
+            self.dash = true;
+        } else if key == bindings.grapple {
+            self.grapple = is_pressed;
+        } else if key == bindings.grenade {
+            self.grenade = is_pressed;
+        } else if key == bindings.spectate && is_pressed && !repeat {
+            self.toggle_spectator(ctx);
+        } else if key == bindings.spectate_next && is_pressed && self.spectating {
+            self.cycle_spectate_target(ctx, 1);
+        } else if key == bindings.spectate_previous && is_pressed && self.spectating {
+            self.cycle_spectate_target(ctx, -1);
+        } else if key == bindings.camera_mode && is_pressed && !repeat {
+            self.toggle_camera_mode(ctx);
+        } else if key == bindings.melee && is_pressed && !repeat {
+            self.melee = true;
+        } else if key == bindings.flashlight && is_pressed && !repeat {
+            self.flashlight_on = !self.flashlight_on && self.battery > 0.0;
+        } else if let Some(index) = weapon_slot_key(key) {
+            if is_pressed && !repeat {
+                self.switch_weapon(ctx, index);
+            }
+        }
+    }
+    // ANCHOR_END: action_keys
 
-    #[visit(optional)]
-    #[reflect(hidden)]
-    pitch: f32,
-    // ANCHOR_END: input_fields
+    // ANCHOR: try_shoot
+    // Fires the selected weapon if the player wants to and it is possible, returns `true` if the
+    // weapon has fired. The weapon node of the selected slot becomes the current weapon, which is
+    // asked to shoot.
+    fn try_shoot(&mut self, ctx: &mut ScriptContext) -> bool {
+        let window = self.config.action_buffer;
+        self.shot_timer -= ctx.dt;
+        let can_shoot = self.shot_timer <= 0.0
+            && !self.is_reloading()
+            && !self.is_mantling()
+            && self
+                .current_weapon()
+                .map_or(false, |weapon| weapon.ammo > 0);
+        let buffered_shot =
+            self.action_buffer
+                .take(BufferedAction::Fire, ctx.elapsed_time, window, can_shoot);
+        let wants_shoot = self.fire_held || buffered_shot;
+        let Some(weapon) = self
+            .weapons
+            .get_mut(self.selected_weapon)
+            .filter(|_| can_shoot && wants_shoot)
+        else {
+            return false;
+        };
+        // Holding the button fires at a fixed cadence, not every frame.
+        self.shot_timer = 1.0 / weapon.fire_rate.max(f32::EPSILON);
+        weapon.ammo -= 1;
+        let damage = weapon.damage;
+        self.current_weapon.set_value_silent(weapon.weapon);
 
-    // ANCHOR: camera_field
-    #[visit(optional)]
-    camera: Handle<Node>,
-    // ANCHOR_END: camera_field
+        match self.fire_mode {
+            FireMode::HitScan => self.fire(ctx, damage),
+            FireMode::Projectile => self.launch_projectile(ctx),
+        }
+        self.add_recoil();
+        self.add_shot_spread();
+        self.muzzle_flash_timer = MUZZLE_FLASH_DURATION;
+        self.spawn_muzzle_effect(ctx);
 
-    // ANCHOR: current_weapon_field
-    #[visit(optional)]
-    current_weapon: InheritableVariable<Handle<Node>>,
-    // ANCHOR_END: current_weapon_field
-    #[visit(optional)]
-    #[reflect(hidden)]
-    shoot: bool,
+        true
+    }
+    // ANCHOR_END: try_shoot
+
+    // ANCHOR: jumping
+    // Returns `true` if the player jumps in this frame.
+    fn update_jump(&mut self, dt: f32) -> bool {
+        // Jumping is allowed only when the player stands on something, otherwise it would be
+        // possible to jump infinitely in the air. It is also impossible to jump off steep
+        // slopes, this way they cannot be climbed.
+        let on_walkable_ground = self.is_on_walkable_ground();
+        if on_walkable_ground {
+            self.coyote_timer = self.config.coyote_time;
+            self.air_jumps_used = 0;
+        }
+        let has_stamina = self.stamina >= self.config.jump_stamina_cost;
+        let mut can_jump =
+            has_stamina && is_jump_allowed(self.coyote_timer, self.jump_buffer_timer);
+        if !can_jump
+            && has_stamina
+            && self.jump_buffer_timer >= 0.0
+            && self.air_jumps_used < self.config.max_air_jumps
+        {
+            // Air jumps set the vertical velocity just like normal ones, so they are not affected
+            // by falling speed.
+            can_jump = true;
+            self.air_jumps_used += 1;
+        }
+        if can_jump {
+            // Both windows are closed, so a single press could not make several jumps.
+            self.coyote_timer = -1.0;
+            self.jump_buffer_timer = -1.0;
+            self.stamina -= self.config.jump_stamina_cost;
+        }
+        self.coyote_timer -= dt;
+        self.jump_buffer_timer -= dt;
+
+        can_jump
+    }
+    // ANCHOR_END: jumping
+
+    // ANCHOR: launch_on_update
+    // A launch works like a jump, the coyote time must not give the player one more jump.
+    fn take_launch(&mut self) -> Option<Vector3<f32>> {
+        let launch = self.launch_velocity.take();
+        if launch.is_some() {
+            self.coyote_timer = -1.0;
+        } else if self.is_on_walkable_ground() && self.ground_snap_delay <= 0.0 {
+            // The player still touches the ground for a moment after a launch, the snap delay
+            // covers that time.
+            self.exceed_max_speed = false;
+        }
+
+        launch
+    }
+    // ANCHOR_END: launch_on_update
+
+    // ANCHOR: movement_velocity
+    // Gravity would drag the player down a slope even if there's no movement input, so it is
+    // disabled while the player stands still on a walkable surface.
+    fn holds_in_place(&self, can_jump: bool, launched: bool) -> bool {
+        self.is_on_walkable_ground()
+            && self.wish_dir.norm() <= f32::EPSILON
+            && !can_jump
+            && !launched
+    }
+
+    // Calculates the new velocity of the player from the current one and the movement input.
+    fn movement_velocity(
+        &self,
+        current_velocity: Vector3<f32>,
+        can_jump: bool,
+        launch: Option<Vector3<f32>>,
+        gravity: Vector3<f32>,
+        dt: f32,
+    ) -> Vector3<f32> {
+        let on_walkable_ground = self.is_on_walkable_ground();
+
+        // Sprinting and crouching affect only horizontal movement, jumping and falling stays
+        // the same.
+        let mut target_velocity = self.wish_dir.scale(self.movement_speed());
+        target_velocity.y = 0.0;
+        if on_walkable_ground {
+            // Move along the slope, not into it or off it.
+            let normal = self.ground_normal;
+            target_velocity -= normal.scale(target_velocity.dot(&normal));
+        }
+
+        let is_moving = self.wish_dir.norm() > f32::EPSILON;
+
+        let mut y_vel = current_velocity.y;
+        if can_jump {
+            y_vel = self.config.jump_vel;
+        } else if on_walkable_ground && target_velocity.y < 0.0 {
+            // Follow the slope when walking down, otherwise the player would hop down
+            // instead of walking. Falling must not be slowed down though.
+            y_vel = y_vel.min(target_velocity.y);
+        }
+        if let Some(launch) = launch {
+            y_vel = launch.y;
+        }
+
+        let hold_in_place = self.holds_in_place(can_jump, launch.is_some());
+
+        // ANCHOR: gravity_scale
+        // The scene gravity is applied by the physics, only the difference is added here, so
+        // other bodies are not affected.
+        if !hold_in_place && !can_jump && launch.is_none() {
+            y_vel += gravity.y * (self.config.gravity_scale - 1.0) * dt;
+        }
+        // ANCHOR_END: gravity_scale
+
+        // ANCHOR: terminal_velocity
+        y_vel = y_vel.max(-self.config.terminal_velocity);
+        // ANCHOR_END: terminal_velocity
+
+        // Accelerate when there's some movement input, otherwise slow down due to friction.
+        // When the player is not moving, this holds it in-place in the XZ plane. There's no
+        // friction in the air or on steep slopes, so the player keeps its momentum while
+        // falling or sliding.
+        let rate = if is_moving {
+            // ANCHOR: air_control
+            if self.grounded {
+                self.config.acceleration
+            } else {
+                self.config.acceleration * self.config.air_control.clamp(0.0, 1.0)
+            }
+            // ANCHOR_END: air_control
+        } else if on_walkable_ground {
+            self.config.friction
+        } else {
+            0.0
+        };
+        let horizontal_velocity =
+            approach_velocity(current_velocity.xz(), target_velocity.xz(), rate, dt);
+        let mut x_vel = horizontal_velocity.x;
+        let mut z_vel = horizontal_velocity.y;
+
+        // ANCHOR: bhop
+        if self.config.bhop_enabled {
+            let horizontal_velocity = Vector3::new(current_velocity.x, 0.0, current_velocity.z);
+            let wish_dir = Vector3::new(self.wish_dir.x, 0.0, self.wish_dir.z);
+            let velocity = if !self.grounded {
+                match wish_dir.try_normalize(f32::EPSILON) {
+                    Some(direction) => air_accelerate(
+                        horizontal_velocity,
+                        direction,
+                        self.movement_speed() * wish_dir.norm(),
+                        self.config.bhop_air_speed,
+                        self.config.bhop_air_acceleration,
+                        dt,
+                    ),
+                    None => horizontal_velocity,
+                }
+            } else if can_jump {
+                // The ground friction would eat the speed, but the player leaves the ground
+                // right away.
+                horizontal_velocity
+            } else {
+                Vector3::new(x_vel, 0.0, z_vel)
+            };
+            x_vel = velocity.x;
+            z_vel = velocity.z;
+        }
+        // ANCHOR_END: bhop
+
+        // ANCHOR: slide_velocity
+        // The movement input is ignored during a slide, it just slowly loses the speed.
+        if self.sliding {
+            let decay = (-self.config.slide_friction * dt).exp();
+            x_vel = current_velocity.x * decay;
+            z_vel = current_velocity.z * decay;
+        }
+        // ANCHOR_END: slide_velocity
+
+        // Movement input is ignored during a dash, but gravity still works.
+        if self.is_dashing() {
+            let dash_velocity = self.dash_direction.scale(self.config.dash_speed);
+            x_vel = dash_velocity.x;
+            z_vel = dash_velocity.z;
+        }
+
+        if let Some(launch) = launch {
+            x_vel += launch.x;
+            z_vel += launch.z;
+        }
+
+        // ANCHOR: max_speed
+        let horizontal_speed = (x_vel * x_vel + z_vel * z_vel).sqrt();
+        let max_speed = self.config.max_speed;
+        if max_speed > 0.0 && horizontal_speed > max_speed && !self.exceed_max_speed {
+            let scale = max_speed / horizontal_speed;
+            x_vel *= scale;
+            z_vel *= scale;
+        }
+        // ANCHOR_END: max_speed
+
+        Vector3::new(x_vel, y_vel, z_vel)
+    }
+    // ANCHOR_END: movement_velocity
+
+    // ANCHOR: after_movement
+    // Everything that depends on the new velocity of the player. `jumped` is set when the player
+    // has left the ground by a jump or a launch.
+    fn update_after_movement(&mut self, ctx: &mut ScriptContext, jumped: bool) {
+        // ANCHOR: ground_snap_on_update
+        if jumped {
+            self.ground_snap_delay = GROUND_SNAP_JUMP_DELAY;
+        }
+        self.ground_snap_delay -= ctx.dt;
+        self.snap_to_ground(ctx);
+        // ANCHOR_END: ground_snap_on_update
+
+        // ANCHOR: step_climbing_on_update
+        self.climb_steps(ctx);
+        // ANCHOR_END: step_climbing_on_update
+
+        // ANCHOR: head_bob_on_update
+        let horizontal_speed = ctx
+            .scene
+            .graph
+            .try_get_of_type::<RigidBody>(ctx.handle)
+            .map_or(0.0, |rigid_body| rigid_body.lin_vel().xz().norm());
+        self.update_head_bob(ctx, horizontal_speed);
+        // ANCHOR_END: head_bob_on_update
+
+        // ANCHOR: footsteps_on_update
+        self.update_footsteps(ctx, horizontal_speed);
+        // ANCHOR_END: footsteps_on_update
+
+        // ANCHOR: weapon_bob_on_update
+        self.update_weapon_bob(ctx, horizontal_speed);
+        // ANCHOR_END: weapon_bob_on_update
+    }
+    // ANCHOR_END: after_movement
 }
 
 impl ScriptTrait for Player {
+    // ANCHOR: on_start
+    fn on_start(&mut self, ctx: &mut ScriptContext) {
+        self.start_position = ctx.scene.graph[ctx.handle].global_position();
+
+        // ANCHOR: weapon_slots_on_start
+        // Players that were set up with a single weapon node get it as the only slot, with the
+        // default stats. The weapon node is its model as well.
+        if self.weapons.is_empty() && ctx.scene.graph.is_valid_handle(*self.current_weapon) {
+            self.weapons.push(WeaponSlot {
+                weapon: *self.current_weapon,
+                model: *self.current_weapon,
+                ..Default::default()
+            });
+        }
+        // ANCHOR_END: weapon_slots_on_start
+
+        // ANCHOR: cursor_grab_on_start
+        if self.config.grab_cursor {
+            set_cursor_grab(ctx, true);
+        }
+        // ANCHOR_END: cursor_grab_on_start
+
+        // Remember the standing pose, it is used to restore the player after crouching.
+        if let Some(camera) = ctx.scene.graph.try_get(self.camera) {
+            self.first_person_camera_position = **camera.local_transform().position();
+            self.stand_camera_height = self.first_person_camera_position.y;
+        }
+        if let Some(model) = ctx.scene.graph.try_get_mut(*self.model) {
+            model.set_enabled(self.third_person);
+        }
+
+        // ANCHOR: carried_state_on_start
+        // The player came from another level through an exit.
+        if let Some(state) = ctx.plugins.get_mut::<Game>().carried_state.take() {
+            self.load_inventory(&state);
+        }
+        // ANCHOR_END: carried_state_on_start
+
+        for weapon in self.weapons.iter_mut() {
+            weapon.ammo = weapon.ammo.min(weapon.magazine_size);
+            if let Some(model) = ctx.scene.graph.try_get(weapon.model) {
+                weapon.rest_position = **model.local_transform().position();
+            }
+        }
+        self.selected_weapon = self
+            .selected_weapon
+            .min(self.weapons.len().saturating_sub(1));
+        self.update_weapon_models(&mut ctx.scene.graph);
+        self.sway_prev_yaw = self.smooth_yaw;
+        self.sway_prev_pitch = self.smooth_pitch;
+
+        let graph = &ctx.scene.graph;
+        if let Some((handle, collider)) = graph[ctx.handle].children().iter().find_map(|handle| {
+            graph
+                .try_get_of_type::<Collider>(*handle)
+                .map(|collider| (*handle, collider))
+        }) {
+            self.collider = handle;
+            if let ColliderShape::Capsule(capsule) = collider.shape() {
+                self.stand_capsule_length = (capsule.end.y - capsule.begin.y).abs();
+                self.stand_capsule_top = capsule.begin.y.max(capsule.end.y) + capsule.radius;
+                self.capsule_bottom = capsule.begin.y.min(capsule.end.y) - capsule.radius;
+                self.capsule_radius = capsule.radius;
+            }
+        }
+    }
+    // ANCHOR_END: on_start
+
     // ANCHOR: on_os_event
-    fn on_os_event(&mut self, event: &Event<()>, _ctx: &mut ScriptContext) {
+    fn on_os_event(&mut self, event: &Event<()>, ctx: &mut ScriptContext) {
+        // ANCHOR: system_event_on_os_event
+        if self.handle_system_event(event, ctx) {
+            return;
+        }
+        // ANCHOR_END: system_event_on_os_event
+
         match event {
             // Raw mouse input is responsible for camera rotation.
             Event::DeviceEvent {
@@ -72,9 +3962,10 @@ impl ScriptTrait for Player {
             } => {
                 // Pitch is responsible for vertical camera rotation. It has -89.9..89.0 degree limits,
                 // to prevent infinite rotation.
-                let mouse_speed = 0.35;
-                self.pitch = (self.pitch + *dy as f32 * mouse_speed).clamp(-89.9, 89.9);
-                self.yaw -= *dx as f32 * mouse_speed;
+                let dy = if self.config.invert_y { -*dy } else { *dy };
+                let sensitivity = self.config.mouse_sensitivity * self.look_sensitivity_scale();
+                self.pitch = (self.pitch + dy as f32 * sensitivity).clamp(-89.9, 89.9);
+                self.yaw -= *dx as f32 * sensitivity;
             }
             // Keyboard input is responsible for player's movement.
             Event::WindowEvent {
@@ -83,20 +3974,18 @@ impl ScriptTrait for Player {
             } => {
                 if let PhysicalKey::Code(code) = event.physical_key {
                     let is_pressed = event.state == ElementState::Pressed;
-                    match code {
-                        KeyCode::KeyW => {
-                            self.move_forward = is_pressed;
-                        }
-                        KeyCode::KeyS => {
-                            self.move_backward = is_pressed;
-                        }
-                        KeyCode::KeyA => {
-                            self.move_left = is_pressed;
-                        }
-                        KeyCode::KeyD => {
-                            self.move_right = is_pressed;
-                        }
-                        _ => (),
+                    let key = translate_key_to_ui(code);
+                    let bindings = self.key_bindings.clone();
+                    if key == bindings.forward {
+                        self.move_forward = is_pressed;
+                    } else if key == bindings.backward {
+                        self.move_backward = is_pressed;
+                    } else if key == bindings.left {
+                        self.move_left = is_pressed;
+                    } else if key == bindings.right {
+                        self.move_right = is_pressed;
+                    } else {
+                        self.handle_action_key(key, is_pressed, event.repeat, ctx);
                     }
                 }
             }
@@ -119,12 +4008,154 @@ impl ScriptTrait for Player {
             self.shoot = *state == ElementState::Pressed;
         }
         // ANCHOR_END: shooting
+
+        // ANCHOR: fire_buffer_on_os_event
+        // A shot that can't be done right now is remembered, this way it is not lost if the button
+        // was pressed a bit too early.
+        if let Event::WindowEvent {
+            event:
+                WindowEvent::MouseInput {
+                    state,
+                    button: MouseButton::Left,
+                    ..
+                },
+            ..
+        } = event
+        {
+            self.fire_held = *state == ElementState::Pressed;
+            if self.fire_held {
+                self.action_buffer
+                    .push(BufferedAction::Fire, ctx.elapsed_time);
+            }
+        }
+        // ANCHOR_END: fire_buffer_on_os_event
+
+        // ANCHOR: aiming
+        if let Event::WindowEvent {
+            event:
+                WindowEvent::MouseInput {
+                    state,
+                    button: MouseButton::Right,
+                    ..
+                },
+            ..
+        } = event
+        {
+            self.aim = *state == ElementState::Pressed;
+        }
+        // ANCHOR_END: aiming
+
+        // ANCHOR: weapon_scroll_on_os_event
+        // Scrolling up selects the next weapon, scrolling down - the previous one.
+        if let Event::WindowEvent {
+            event: WindowEvent::MouseWheel { delta, .. },
+            ..
+        } = event
+        {
+            let scroll = match delta {
+                MouseScrollDelta::LineDelta(_, y) => *y,
+                MouseScrollDelta::PixelDelta(position) => position.y as f32,
+            };
+            let count = self.weapons.len();
+            if count > 0 && scroll != 0.0 {
+                let index = if scroll > 0.0 {
+                    (self.selected_weapon + 1) % count
+                } else {
+                    (self.selected_weapon + count - 1) % count
+                };
+                self.switch_weapon(ctx, index);
+            }
+        }
+        // ANCHOR_END: weapon_scroll_on_os_event
     }
 
     // ANCHOR: on_update_begin
     fn on_update(&mut self, ctx: &mut ScriptContext) {
         // ANCHOR_END: on_update_begin
 
+        // ANCHOR: pause_on_update
+        if ctx.plugins.get::<Game>().paused {
+            return;
+        }
+        // ANCHOR_END: pause_on_update
+
+        // ANCHOR: cutscene_on_update
+        // The player stands still during a cutscene, but it still could fall.
+        if self.cutscene {
+            if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
+                let velocity = rigid_body.lin_vel();
+                rigid_body.set_lin_vel(Vector3::new(0.0, velocity.y, 0.0));
+            }
+            return;
+        }
+        // ANCHOR_END: cutscene_on_update
+
+        // ANCHOR: time_scale_on_update
+        // Everything the player does is slowed down by the time scale, except looking around,
+        // which uses the real time step, so the controls stay responsive in slow motion.
+        let real_dt = ctx.dt;
+        ctx.dt *= ctx.plugins.get::<Game>().time_scale;
+        // ANCHOR_END: time_scale_on_update
+
+        // ANCHOR: pending_state_on_update
+        self.apply_pending_state(ctx);
+        // ANCHOR_END: pending_state_on_update
+
+        // ANCHOR: death_on_update
+        // Once started, the death camera runs to the end, even if something heals the player.
+        if self.health <= 0.0 || self.is_dying() {
+            if self.update_death_cam(ctx) {
+                return;
+            }
+            self.respawn(ctx);
+        }
+        // ANCHOR_END: death_on_update
+
+        // ANCHOR: ground_check_on_update
+        self.update_ground_state(ctx);
+        // ANCHOR_END: ground_check_on_update
+
+        // ANCHOR: moving_platform_on_update
+        self.ride_platform(ctx);
+        // ANCHOR_END: moving_platform_on_update
+
+        // ANCHOR: contacts_on_update
+        self.update_contacts(ctx);
+        // ANCHOR_END: contacts_on_update
+
+        // ANCHOR: landing_on_update
+        self.update_landing(ctx);
+        // ANCHOR_END: landing_on_update
+
+        // ANCHOR: axes_on_update
+        self.update_axes(ctx, real_dt);
+        // ANCHOR_END: axes_on_update
+
+        // ANCHOR: spectate_on_update
+        if self.spectating {
+            self.spectate(ctx, real_dt);
+            return;
+        }
+        // ANCHOR_END: spectate_on_update
+
+        // ANCHOR: reload_on_update
+        self.update_reload(ctx.dt);
+
+        // Buffered actions are performed as soon as they become available.
+        let window = self.config.action_buffer;
+        let can_reload = self.can_reload();
+        if self
+            .action_buffer
+            .take(BufferedAction::Reload, ctx.elapsed_time, window, can_reload)
+        {
+            self.start_reload();
+        }
+        // ANCHOR_END: reload_on_update
+
+        // ANCHOR: fire_control_on_update
+        self.shoot = self.try_shoot(ctx);
+        // ANCHOR_END: fire_control_on_update
+
         // ANCHOR: shooting_on_update
         if self.shoot {
             ctx.message_sender
@@ -132,6 +4163,53 @@ impl ScriptTrait for Player {
         }
         // ANCHOR_END: shooting_on_update
 
+        // ANCHOR: muzzle_flash_on_update
+        self.update_muzzle_flash(ctx);
+        // ANCHOR_END: muzzle_flash_on_update
+
+        // ANCHOR: tracers_on_update
+        self.update_tracers(ctx);
+        self.update_hit_marker(ctx.dt);
+        // ANCHOR_END: tracers_on_update
+
+        // ANCHOR: damage_indicators_on_update
+        self.update_damage_indicators(ctx);
+        // ANCHOR_END: damage_indicators_on_update
+
+        // ANCHOR: flashlight_on_update
+        self.update_flashlight(ctx);
+        // ANCHOR_END: flashlight_on_update
+
+        // ANCHOR: melee_on_update
+        self.update_melee(ctx);
+        // ANCHOR_END: melee_on_update
+
+        // ANCHOR: grenade_on_update
+        self.update_grenade(ctx);
+        // ANCHOR_END: grenade_on_update
+
+        // ANCHOR: interaction_on_update
+        self.update_interaction(ctx);
+        // ANCHOR_END: interaction_on_update
+
+        // ANCHOR: recoil_on_update
+        self.update_recoil(ctx.dt);
+        self.update_spread(ctx.dt);
+        self.update_shake(ctx.dt);
+        // ANCHOR_END: recoil_on_update
+
+        // ANCHOR: fov_on_update
+        self.update_fov(ctx);
+        // ANCHOR_END: fov_on_update
+
+        // ANCHOR: look_smoothing_on_update
+        self.update_look_smoothing(real_dt);
+        // ANCHOR_END: look_smoothing_on_update
+
+        // ANCHOR: camera_tilt_on_update
+        self.update_camera_tilt(ctx);
+        // ANCHOR_END: camera_tilt_on_update
+
         // ANCHOR: camera_rotation
         let mut look_vector = Vector3::default();
         let mut side_vector = Vector3::default();
@@ -150,37 +4228,256 @@ impl ScriptTrait for Player {
         }
         // ANCHOR_END: camera_rotation
 
+        // ANCHOR: view_rotation_on_update
+        self.update_view_rotation(ctx);
+        // ANCHOR_END: view_rotation_on_update
+
+        // ANCHOR: camera_position_on_update
+        self.update_camera_position(ctx);
+        self.update_character_model(ctx);
+        // ANCHOR_END: camera_position_on_update
+
+        // ANCHOR: weapon_sway_on_update
+        self.update_weapon_sway(ctx, side_vector);
+        // ANCHOR_END: weapon_sway_on_update
+
+        // ANCHOR: noclip_on_update
+        if self.noclip {
+            self.fly(ctx, look_vector, side_vector);
+            return;
+        }
+        // ANCHOR_END: noclip_on_update
+
+        // ANCHOR: crouch_on_update
+        self.update_crouch(ctx);
+        // ANCHOR_END: crouch_on_update
+
+        // ANCHOR: wish_dir_on_update
+        // The third-person camera usually looks down at the player, so only the horizontal part
+        // of its direction is used, otherwise the player would walk slower.
+        let move_look_vector = if self.third_person {
+            Vector3::new(look_vector.x, 0.0, look_vector.z)
+                .try_normalize(f32::EPSILON)
+                .unwrap_or(look_vector)
+        } else {
+            look_vector
+        };
+        self.wish_dir = calculate_wish_dir(self.move_axis, move_look_vector, side_vector);
+        // ANCHOR_END: wish_dir_on_update
+
+        // ANCHOR: stamina_on_update
+        self.update_stamina(ctx.dt);
+        // ANCHOR_END: stamina_on_update
+
+        // ANCHOR: slide_on_update
+        self.update_slide(ctx);
+        // ANCHOR_END: slide_on_update
+
+        // ANCHOR: mantle_on_update
+        if !self.is_on_ladder() && !self.is_swimming() && self.update_mantle(ctx, look_vector) {
+            return;
+        }
+        // ANCHOR_END: mantle_on_update
+
+        // ANCHOR: ladder_on_update
+        if self.is_on_ladder() {
+            self.climb(ctx, side_vector);
+            return;
+        } else if self.climb_velocity != 0.0 {
+            self.climb_off_ladder(ctx, look_vector);
+        }
+        // ANCHOR_END: ladder_on_update
+
+        // ANCHOR: swimming_on_update
+        if self.is_swimming() {
+            self.swim(ctx);
+            return;
+        }
+        // ANCHOR_END: swimming_on_update
+
+        // ANCHOR: grapple_on_update
+        if self.update_grapple(ctx) {
+            return;
+        }
+        // ANCHOR_END: grapple_on_update
+
+        // ANCHOR: wall_run_on_update
+        if self.wall_run(ctx, look_vector, side_vector) {
+            return;
+        }
+        // ANCHOR_END: wall_run_on_update
+
+        // ANCHOR: dash_on_update
+        self.update_dash(ctx.dt, look_vector);
+        // ANCHOR_END: dash_on_update
+
         // ANCHOR: on_update_end
+        let can_jump = self.update_jump(ctx.dt);
+        let launch = self.take_launch();
+        let gravity = *ctx.scene.graph.physics.gravity;
+
         // Borrow the node to which this script is assigned to. We also check if the node is RigidBody.
         if let Some(rigid_body) = ctx.scene.graph.try_get_mut_of_type::<RigidBody>(ctx.handle) {
-            // Form a new velocity vector that corresponds to the pressed buttons.
-            let mut velocity = Vector3::new(0.0, 0.0, 0.0);
-            if self.move_forward {
-                velocity += look_vector;
-            }
-            if self.move_backward {
-                velocity -= look_vector;
-            }
-            if self.move_left {
-                velocity += side_vector;
-            }
-            if self.move_right {
-                velocity -= side_vector;
+            let hold_in_place = self.holds_in_place(can_jump, launch.is_some());
+            rigid_body.set_gravity_scale(if hold_in_place { 0.0 } else { 1.0 });
+
+            let velocity =
+                self.movement_velocity(rigid_body.lin_vel(), can_jump, launch, gravity, ctx.dt);
+            rigid_body.set_lin_vel(velocity);
+        }
+
+        self.update_after_movement(ctx, can_jump || launch.is_some());
+    }
+    // ANCHOR_END: on_update_end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Walks for two seconds from standing still, returns the final speed and the distance.
+    fn walk(dt: f32) -> (f32, f32) {
+        let target = Vector2::new(4.0, 0.0);
+        let mut velocity = Vector2::default();
+        let mut distance = 0.0;
+        for _ in 0..(2.0 / dt).round() as usize {
+            velocity = approach_velocity(velocity, target, 10.0, dt);
+            distance += velocity.x * dt;
+        }
+        (velocity.x, distance)
+    }
+
+    #[test]
+    fn movement_does_not_depend_on_frame_rate() {
+        let (speed_30, distance_30) = walk(1.0 / 30.0);
+        let (speed_120, distance_120) = walk(1.0 / 120.0);
+        assert!((speed_30 - speed_120).abs() < 1.0e-3);
+        assert!((distance_30 - distance_120).abs() < 0.1);
+    }
+
+    const FRAME: f32 = 1.0 / 60.0;
+
+    // Presses the action at the first frame and checks it every frame after that, it becomes
+    // available at the given frame. Returns the frame at which the action was performed.
+    fn buffered_press(window: f32, available_at: usize) -> Option<usize> {
+        let mut buffer = ActionBuffer::default();
+        buffer.push(BufferedAction::Reload, 0.0);
+        (0..60).find(|frame| {
+            let time = *frame as f32 * FRAME;
+            buffer.take(BufferedAction::Reload, time, window, *frame >= available_at)
+        })
+    }
+
+    #[test]
+    fn buffered_press_is_consumed_inside_window() {
+        assert_eq!(buffered_press(0.2, 5), Some(5));
+
+        // It is performed only once.
+        let mut buffer = ActionBuffer::default();
+        buffer.push(BufferedAction::Fire, 0.0);
+        assert!(buffer.take(BufferedAction::Fire, FRAME, 0.2, true));
+        assert!(!buffer.take(BufferedAction::Fire, 2.0 * FRAME, 0.2, true));
+    }
+
+    #[test]
+    fn buffered_press_expires_after_window() {
+        // 0.2 seconds is 12 frames, the action becomes available too late.
+        assert_eq!(buffered_press(0.2, 20), None);
+    }
+
+    #[test]
+    fn buffered_press_is_dropped_on_clear() {
+        let mut buffer = ActionBuffer::default();
+        buffer.push(BufferedAction::Fire, 0.0);
+        // Pausing the game clears the buffer.
+        buffer.clear();
+        assert!(!buffer.take(BufferedAction::Fire, FRAME, 0.2, true));
+    }
+
+    #[test]
+    fn jump_is_allowed_only_inside_both_windows() {
+        // Timers count down from the window length, zero is the last moment of a window.
+        assert!(is_jump_allowed(0.0, 0.0));
+        assert!(is_jump_allowed(0.1, 0.1));
+        assert!(!is_jump_allowed(-0.001, 0.1));
+        assert!(!is_jump_allowed(0.1, -0.001));
+        assert!(!is_jump_allowed(-1.0, -1.0));
+    }
+
+    const LEDGE: usize = 30;
+
+    // Jump windows of 0.1 seconds are 6 frames long, the stamina and air jumps do not interfere.
+    fn jumping_player() -> Player {
+        let mut player = Player::default();
+        player.config.coyote_time = 0.1;
+        player.config.jump_buffer = 0.1;
+        player.config.max_air_jumps = 0;
+        player.config.jump_stamina_cost = 0.0;
+        player
+    }
+
+    // Walks off a ledge at the given frame and presses jump the given number of frames after
+    // that. Returns the frame at which the player jumped.
+    fn jump_after_leaving_ground(press_delay: usize) -> Option<usize> {
+        let mut player = jumping_player();
+        (0..LEDGE + 60).find(|frame| {
+            player.grounded = *frame < LEDGE;
+            if *frame == LEDGE + press_delay {
+                player.jump_buffer_timer = player.config.jump_buffer;
             }
+            player.update_jump(FRAME)
+        })
+    }
 
-            let y_vel = rigid_body.lin_vel().y;
-            if let Some(normalized_velocity) = velocity.try_normalize(f32::EPSILON) {
-                let movement_speed = 240.0 * ctx.dt;
-                rigid_body.set_lin_vel(Vector3::new(
-                    normalized_velocity.x * movement_speed,
-                    y_vel,
-                    normalized_velocity.z * movement_speed,
-                ));
-            } else {
-                // Hold player in-place in XZ plane when no button is pressed.
-                rigid_body.set_lin_vel(Vector3::new(0.0, y_vel, 0.0));
+    // Falls, presses jump the given number of frames before landing at the given frame.
+    // Returns the frame at which the player jumped.
+    fn jump_before_landing(press_advance: usize) -> Option<usize> {
+        let mut player = jumping_player();
+        (0..LEDGE + 60).find(|frame| {
+            player.grounded = *frame >= LEDGE;
+            if *frame + press_advance == LEDGE {
+                player.jump_buffer_timer = player.config.jump_buffer;
             }
+            player.update_jump(FRAME)
+        })
+    }
+
+    #[test]
+    fn jump_fires_inside_coyote_time_only() {
+        for press_delay in 0..4 {
+            assert_eq!(
+                jump_after_leaving_ground(press_delay),
+                Some(LEDGE + press_delay)
+            );
+        }
+        for press_delay in 8..20 {
+            assert_eq!(jump_after_leaving_ground(press_delay), None);
         }
     }
-    // ANCHOR_END: on_update_end
+
+    #[test]
+    fn early_jump_fires_on_landing_inside_jump_buffer_only() {
+        for press_advance in 0..4 {
+            assert_eq!(jump_before_landing(press_advance), Some(LEDGE));
+        }
+        // The press has expired by the landing and is not performed later either.
+        for press_advance in 8..20 {
+            assert_eq!(jump_before_landing(press_advance), None);
+        }
+    }
+
+    #[test]
+    fn diagonal_wish_dir_is_normalized() {
+        let look = Vector3::z();
+        let side = Vector3::x();
+
+        let diagonal = calculate_wish_dir(Vector2::new(1.0, 1.0), look, side);
+        assert!((diagonal.norm() - 1.0).abs() < 1.0e-5);
+        // The side vector points left, so moving right goes along the negative X axis.
+        assert!(diagonal.x < 0.0 && diagonal.z > 0.0);
+
+        // Partial analog input is kept as is.
+        let half = calculate_wish_dir(Vector2::new(0.0, 0.5), look, side);
+        assert!((half.norm() - 0.5).abs() < 1.0e-5);
+    }
 }
@@ -0,0 +1,453 @@
+use crate::storage::{load_visit, save_visit};
+use fyrox::core::{
+    reflect::prelude::*,
+    visitor::{prelude::*, VisitError},
+};
+use std::path::Path;
+
+// ANCHOR: player_config
+// All the tunable parameters of the player. They are kept separately from the state of the
+// player, this way the same settings could be shared across levels or loaded from a file.
+#[derive(Visit, Reflect, Debug, Clone)]
+#[visit(optional)]
+pub struct PlayerConfig {
+    // Horizontal speed of the player when it walks, in meters per second.
+    pub walk_speed: f32,
+
+    // Vertical velocity that will be given to the player's body when it jumps.
+    pub jump_vel: f32,
+
+    // Multiplier of the scene gravity for the player only, values below 1.0 make it floaty and
+    // above 1.0 make it heavy. Jump height is `jump_vel^2 / (2 * gravity * gravity_scale)`.
+    pub gravity_scale: f32,
+
+    // Maximum falling speed, it keeps the player from passing through thin floors on long falls.
+    // Upward speed is not limited.
+    pub terminal_velocity: f32,
+
+    // Maximum horizontal speed of the player, it is applied after all kinds of movement are added
+    // up. Launches could exceed it until the player lands. Zero disables the limit.
+    pub max_speed: f32,
+
+    // Time (in seconds) after leaving the ground, during which the player still can jump.
+    pub coyote_time: f32,
+
+    // Time (in seconds) during which a jump press is remembered, this way pressing the key right
+    // before landing still makes the player jump.
+    pub jump_buffer: f32,
+
+    // How many times the player can jump in the air before landing.
+    pub max_air_jumps: u32,
+
+    // Horizontal speed of the player during a dash.
+    pub dash_speed: f32,
+
+    // How long (in seconds) a dash lasts.
+    pub dash_duration: f32,
+
+    // Time (in seconds) after the start of a dash, before the next one is allowed.
+    pub dash_cooldown: f32,
+
+    // Speed of the player along a wall.
+    pub wall_run_speed: f32,
+
+    // Maximum time (in seconds) of running on walls between two landings.
+    pub wall_run_duration: f32,
+
+    // Maximum distance at which the grappling hook could attach to something.
+    pub grapple_range: f32,
+
+    // Stiffness of the grapple spring, the higher it is the faster the player is pulled.
+    pub grapple_pull: f32,
+
+    // How much faster the player moves while sprinting.
+    pub sprint_multiplier: f32,
+
+    pub max_stamina: f32,
+
+    // Stamina spent per second of sprinting.
+    pub stamina_drain: f32,
+
+    // Stamina spent on each jump, the player cannot jump without enough stamina.
+    pub jump_stamina_cost: f32,
+
+    // Stamina restored per second when the player is not sprinting.
+    pub stamina_regen: f32,
+
+    // When stamina runs out, sprinting is disabled until it recovers to this value.
+    pub stamina_recovery_threshold: f32,
+
+    // If set, stamina does not regenerate while the player is in the air.
+    pub strict_stamina_regen: bool,
+
+    // If set, sprinting works only when the player moves forward.
+    pub sprint_forward_only: bool,
+
+    // If set, the sprint key switches sprinting on and off instead of being held.
+    pub sprint_toggle: bool,
+
+    // If set, the crouch key switches crouching on and off instead of being held.
+    pub crouch_toggle: bool,
+
+    // How fast the player moves while crouching, relative to the normal speed.
+    pub crouch_multiplier: f32,
+
+    // Local height of the camera when the player is fully crouched.
+    pub crouch_camera_height: f32,
+
+    // Height of the capsule collider when crouched, relative to its standing height.
+    pub crouch_collider_scale: f32,
+
+    // Time (in seconds) that is needed to fully crouch or stand up.
+    pub crouch_time: f32,
+
+    // Horizontal speed that is added to the current velocity when the player crouches while
+    // sprinting. Zero disables sliding.
+    pub slide_boost: f32,
+
+    // How fast the slide slows down, it is much lower than the normal friction.
+    pub slide_friction: f32,
+
+    // Degrees of camera rotation per one unit of raw mouse movement.
+    pub mouse_sensitivity: f32,
+
+    // Flips vertical mouse movement, so moving the mouse up makes the player look down.
+    pub invert_y: bool,
+
+    // Maximum vertical offset of the camera when walking.
+    pub bob_amplitude: f32,
+
+    // How many head bob cycles there are in one second of walking.
+    pub bob_frequency: f32,
+
+    // Maximum sideways and vertical offsets of the weapon model when walking.
+    pub weapon_bob_horizontal: f32,
+    pub weapon_bob_vertical: f32,
+
+    // Degrees per second of camera rotation when a gamepad stick is fully deflected.
+    pub gamepad_look_speed: f32,
+
+    // How fast the player reaches its target speed when there's movement input.
+    pub acceleration: f32,
+
+    // How fast the player stops when there's no movement input.
+    pub friction: f32,
+
+    // Part of the acceleration (from 0 to 1) that the movement input gives in the air. At 0 the
+    // player keeps the momentum it had when it left the ground, at 1 it moves just like on it.
+    pub air_control: f32,
+
+    // Quake-style movement: the velocity in the air is only added to, not replaced, and jumping
+    // right on landing skips the ground friction, so chained jumps keep and build up the speed.
+    pub bhop_enabled: bool,
+
+    // Acceleration in the air when bunny-hopping is enabled, it is relative to the walk speed.
+    pub bhop_air_acceleration: f32,
+
+    // Maximum speed (in meters per second) along the wish direction that the air acceleration
+    // could give. Turning while strafing changes the wish direction, this is how the speed builds
+    // up.
+    pub bhop_air_speed: f32,
+
+    // Maximum distance from the body to the ground at which the player is considered grounded.
+    pub ground_check_distance: f32,
+
+    // Steepest slope (in degrees) the player can walk on.
+    pub max_walkable_slope: f32,
+
+    // Maximum distance at which the player can interact with objects.
+    pub interaction_range: f32,
+
+    // Initial speed of launched projectiles.
+    pub projectile_speed: f32,
+
+    // If set, the damage of each hit is shown as a number floating at the hit point.
+    pub show_damage_numbers: bool,
+
+    // Time (in seconds) that is needed to reload the magazine.
+    pub reload_time: f32,
+
+    // Time (in seconds) during which a fire or reload press is remembered, this way pressing it
+    // a bit too early (for example, right before the end of a reload) still works.
+    pub action_buffer: f32,
+
+    // How much the camera kicks up (in degrees) with each shot.
+    pub recoil_per_shot: f32,
+
+    // How fast the camera returns back after the recoil.
+    pub recoil_recovery: f32,
+
+    // Angle (in degrees) of the cone in which hit-scan shots go when the player stands still.
+    pub base_spread: f32,
+
+    // Spread (in degrees) that is added when the player moves at full speed.
+    pub moving_spread: f32,
+
+    // Spread (in degrees) that is added while the player is in the air.
+    pub air_spread: f32,
+
+    // Spread (in degrees) that every shot adds, so rapid fire gets less accurate.
+    pub shot_spread: f32,
+
+    // Limit (in degrees) of the spread from the shots.
+    pub max_shot_spread: f32,
+
+    // How fast (in degrees per second) the spread from the shots goes away.
+    pub spread_recovery: f32,
+
+    // Spread is multiplied by these when the player crouches or aims down sights.
+    pub crouch_spread_scale: f32,
+    pub ads_spread_scale: f32,
+
+    // Maximum angle (in degrees) of the screen shake at full trauma.
+    pub shake_max_angle: f32,
+
+    // How much trauma goes away per second.
+    pub shake_decay: f32,
+
+    // How fast the camera shakes, bigger values make it more jittery.
+    pub shake_frequency: f32,
+
+    // Amount of health that a melee hit takes.
+    pub melee_damage: f32,
+
+    // Maximum distance at which a melee attack hits something.
+    pub melee_range: f32,
+
+    // Minimum time (in seconds) between two melee attacks.
+    pub melee_cooldown: f32,
+
+    // Impulse that a melee hit gives to the rigid body of the target. Zero disables knockback.
+    pub melee_knockback: f32,
+
+    // Speed (in m/s) of a thrown grenade along the look vector and upwards, the latter makes it fly
+    // in an arc.
+    pub grenade_throw_speed: f32,
+    pub grenade_toss_speed: f32,
+
+    pub max_health: f32,
+
+    // For how long (in seconds) the camera orbits the place of death before the player respawns.
+    pub death_cam_duration: f32,
+
+    // How fast the player swims in the look direction.
+    pub swim_speed: f32,
+
+    // Upward acceleration that the player gets in water.
+    pub buoyancy: f32,
+
+    // Vertical speed of the player on ladders.
+    pub climb_speed: f32,
+
+    // Time (in seconds) between two steps when walking at the normal speed.
+    pub footstep_interval: f32,
+
+    // Minimum falling speed at which landing is noticeable.
+    pub landing_speed_threshold: f32,
+
+    // Landings slower than this do not hurt.
+    pub safe_fall_speed: f32,
+
+    // Amount of damage per one unit of landing speed above the safe one.
+    pub fall_damage_scale: f32,
+
+    // Maximum height of an obstacle that the player can step on without jumping.
+    pub max_step_height: f32,
+
+    // Maximum height of a ledge (above the feet) that the player can grab in the air and climb
+    // onto. Zero disables mantling.
+    pub mantle_height: f32,
+
+    // Time (in seconds) that the player needs to climb onto a ledge.
+    pub mantle_time: f32,
+
+    // Maximum distance at which the player is pulled down to the ground, when it walks off a step
+    // or down a slope. This way it does not hop down.
+    pub snap_distance: f32,
+
+    // If set, the cursor is hidden and locked inside the window while the game is running.
+    pub grab_cursor: bool,
+
+    // Vertical field of view of the camera (in degrees).
+    pub fov: f32,
+
+    // Field of view when aiming down sights.
+    pub ads_fov: f32,
+
+    // How fast the field of view changes when the player starts or stops aiming.
+    pub ads_speed: f32,
+
+    // Degrees that are added to the field of view while sprinting, aiming down sights overrides it.
+    pub sprint_fov_bonus: f32,
+
+    // Time (in seconds) that the camera needs to catch up with the look input. Zero disables
+    // smoothing.
+    pub look_smoothing: f32,
+
+    // If set, the minimap turns with the player, so the forward direction is always up. Otherwise
+    // north (positive Z axis) is up.
+    pub minimap_rotation: bool,
+
+    // Distance (in meters) from the player to the edge of the minimap.
+    pub minimap_range: f32,
+
+    pub noclip_speed: f32,
+
+    // For how long (in seconds) the flashlight could shine on a full battery.
+    pub flashlight_battery: f32,
+
+    // How many seconds of light the battery restores per second while the flashlight is off.
+    pub flashlight_recharge: f32,
+
+    // The flashlight starts to flicker when the battery charge goes below this fraction.
+    pub flashlight_flicker_threshold: f32,
+
+    // Distance from the player to the camera in the third-person mode.
+    pub camera_distance: f32,
+
+    // Time (in seconds) that the character model needs to turn to the movement direction in the
+    // third-person mode. Zero makes it turn instantly.
+    pub model_rotation_smoothing: f32,
+
+    // Maximum offset of the weapon model when the player turns or strafes.
+    pub sway_amount: f32,
+
+    // How fast the weapon model follows the sway offset and springs back to rest.
+    pub sway_smoothing: f32,
+
+    // Part of the sway and bob of the weapon model that remains while aiming down sights.
+    pub ads_sway_scale: f32,
+
+    // Roll of the camera (in degrees) when strafing at full speed, the camera leans into the
+    // movement. Zero disables it.
+    pub strafe_tilt: f32,
+
+    // Forward tilt of the camera (in degrees) while sprinting. Zero disables it.
+    pub sprint_tilt: f32,
+
+    // How fast the camera tilts in and out.
+    pub tilt_smoothing: f32,
+}
+// ANCHOR_END: player_config
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            walk_speed: 4.0,
+            jump_vel: 5.0,
+            gravity_scale: 1.0,
+            terminal_velocity: 40.0,
+            max_speed: 20.0,
+            coyote_time: 0.1,
+            jump_buffer: 0.1,
+            max_air_jumps: 0,
+            dash_speed: 15.0,
+            dash_duration: 0.2,
+            dash_cooldown: 1.0,
+            wall_run_speed: 8.0,
+            wall_run_duration: 1.5,
+            grapple_range: 30.0,
+            grapple_pull: 6.0,
+            sprint_multiplier: 1.6,
+            max_stamina: 100.0,
+            stamina_drain: 20.0,
+            jump_stamina_cost: 10.0,
+            stamina_regen: 15.0,
+            stamina_recovery_threshold: 30.0,
+            strict_stamina_regen: false,
+            sprint_forward_only: true,
+            sprint_toggle: false,
+            crouch_toggle: false,
+            crouch_multiplier: 0.5,
+            crouch_camera_height: 0.5,
+            crouch_collider_scale: 0.5,
+            crouch_time: 0.2,
+            slide_boost: 4.0,
+            slide_friction: 1.5,
+            mouse_sensitivity: 0.35,
+            invert_y: false,
+            bob_amplitude: 0.05,
+            bob_frequency: 2.0,
+            weapon_bob_horizontal: 0.01,
+            weapon_bob_vertical: 0.015,
+            gamepad_look_speed: 180.0,
+            acceleration: 10.0,
+            friction: 10.0,
+            air_control: 1.0,
+            bhop_enabled: false,
+            bhop_air_acceleration: 10.0,
+            bhop_air_speed: 1.0,
+            ground_check_distance: 1.0,
+            max_walkable_slope: 45.0,
+            interaction_range: 2.0,
+            projectile_speed: 30.0,
+            show_damage_numbers: true,
+            reload_time: 1.5,
+            action_buffer: 0.15,
+            recoil_per_shot: 1.5,
+            recoil_recovery: 8.0,
+            base_spread: 0.5,
+            moving_spread: 2.0,
+            air_spread: 4.0,
+            shot_spread: 0.6,
+            max_shot_spread: 5.0,
+            spread_recovery: 8.0,
+            crouch_spread_scale: 0.6,
+            ads_spread_scale: 0.3,
+            shake_max_angle: 3.0,
+            shake_decay: 1.0,
+            shake_frequency: 20.0,
+            melee_damage: 35.0,
+            melee_range: 1.5,
+            melee_cooldown: 0.6,
+            melee_knockback: 5.0,
+            grenade_throw_speed: 12.0,
+            grenade_toss_speed: 4.0,
+            max_health: 100.0,
+            death_cam_duration: 3.0,
+            swim_speed: 3.0,
+            buoyancy: 2.0,
+            climb_speed: 2.5,
+            footstep_interval: 0.5,
+            landing_speed_threshold: 3.0,
+            safe_fall_speed: 12.0,
+            fall_damage_scale: 5.0,
+            max_step_height: 0.3,
+            mantle_height: 1.5,
+            mantle_time: 0.4,
+            snap_distance: 0.5,
+            grab_cursor: true,
+            fov: 75.0,
+            ads_fov: 40.0,
+            ads_speed: 12.0,
+            sprint_fov_bonus: 8.0,
+            look_smoothing: 0.0,
+            minimap_rotation: true,
+            minimap_range: 50.0,
+            noclip_speed: 10.0,
+            flashlight_battery: 120.0,
+            flashlight_recharge: 5.0,
+            flashlight_flicker_threshold: 0.2,
+            camera_distance: 4.0,
+            model_rotation_smoothing: 0.1,
+            sway_amount: 0.03,
+            sway_smoothing: 8.0,
+            ads_sway_scale: 0.2,
+            strafe_tilt: 0.0,
+            sprint_tilt: 0.0,
+            tilt_smoothing: 8.0,
+        }
+    }
+}
+
+// ANCHOR: player_config_io
+impl PlayerConfig {
+    pub fn save(&mut self, path: &Path) -> VisitResult {
+        save_visit(self, "PlayerConfig", path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, VisitError> {
+        load_visit("PlayerConfig", path)
+    }
+}
+// ANCHOR_END: player_config_io
@@ -0,0 +1,41 @@
+use crate::{
+    item::ItemId,
+    storage::{load_visit, save_visit},
+};
+use fyrox::core::{
+    algebra::Vector3,
+    reflect::prelude::*,
+    visitor::{prelude::*, VisitError},
+};
+use std::path::Path;
+
+// ANCHOR: player_state
+// A snapshot of the player that could be saved to disk and restored later.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+#[visit(optional)]
+pub struct PlayerState {
+    pub position: Vector3<f32>,
+    pub velocity: Vector3<f32>,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub health: f32,
+    pub current_weapon: usize,
+    // Rounds in the magazine of each weapon.
+    pub weapon_ammo: Vec<u32>,
+    pub reserve_ammo: u32,
+    pub grenade_count: u32,
+    pub items: Vec<ItemId>,
+}
+// ANCHOR_END: player_state
+
+// ANCHOR: player_state_io
+impl PlayerState {
+    pub fn save(&mut self, path: &Path) -> VisitResult {
+        save_visit(self, "PlayerState", path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, VisitError> {
+        load_visit("PlayerState", path)
+    }
+}
+// ANCHOR_END: player_state_io
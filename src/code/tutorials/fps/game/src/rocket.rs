@@ -0,0 +1,110 @@
+use crate::{effect::spawn_effect, explosion::explode, health::find_health_mut};
+use fyrox::{
+    core::{
+        math, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    resource::model::ModelResource,
+    scene::{collider::Collider, node::Node},
+    script::{ScriptContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "8852f25e-8af2-470d-8b13-836d6345d0eb")]
+#[visit(optional)]
+pub struct Rocket {
+    // ANCHOR: rocket_fields
+    // Amount of health that the rocket takes from the thing it hits.
+    damage: InheritableVariable<f32>,
+
+    // Everything within this radius is hurt by the explosion, even without a direct hit.
+    explosion_radius: InheritableVariable<f32>,
+    explosion_damage: InheritableVariable<f32>,
+
+    // The rocket will be removed after this amount of seconds, even if it did not hit anything.
+    lifetime: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    age: f32,
+
+    impact_effect: InheritableVariable<Option<ModelResource>>,
+    // ANCHOR_END: rocket_fields
+}
+
+impl Default for Rocket {
+    fn default() -> Self {
+        Self {
+            damage: 50.0.into(),
+            explosion_radius: 3.0.into(),
+            explosion_damage: 30.0.into(),
+            lifetime: 10.0.into(),
+            age: 0.0,
+            impact_effect: Default::default(),
+        }
+    }
+}
+
+impl Rocket {
+    // ANCHOR: find_contact
+    // Returns a collider of something that the rocket touched.
+    fn find_contact(&self, ctx: &ScriptContext) -> Option<Handle<Node>> {
+        let graph = &ctx.scene.graph;
+        graph[ctx.handle].children().iter().find_map(|child| {
+            let collider = graph.try_get_of_type::<Collider>(*child)?;
+            collider
+                .contacts(&graph.physics)
+                .find(|contact| contact.has_any_active_contact)
+                .map(|contact| {
+                    if contact.collider1 == *child {
+                        contact.collider2
+                    } else {
+                        contact.collider1
+                    }
+                })
+        })
+    }
+    // ANCHOR_END: find_contact
+}
+
+impl ScriptTrait for Rocket {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        // ANCHOR: lifetime
+        self.age += ctx.dt;
+        if self.age >= *self.lifetime {
+            ctx.scene.graph.remove_node(ctx.handle);
+            return;
+        }
+        // ANCHOR_END: lifetime
+
+        // ANCHOR: impact
+        let Some(other) = self.find_contact(ctx) else {
+            return;
+        };
+
+        if let Some(health) = find_health_mut(&mut ctx.scene.graph, other) {
+            health.damage(*self.damage);
+        }
+
+        let node = &ctx.scene.graph[ctx.handle];
+        let position = node.global_position();
+        let direction = -node.look_vector();
+        spawn_effect(
+            ctx.scene,
+            self.impact_effect.as_ref(),
+            position,
+            math::vector_to_quat(direction),
+        );
+
+        let position = ctx.scene.graph[ctx.handle].global_position();
+        explode(
+            ctx,
+            position,
+            *self.explosion_radius,
+            *self.explosion_damage,
+            true,
+        );
+
+        ctx.scene.graph.remove_node(ctx.handle);
+        // ANCHOR_END: impact
+    }
+}
@@ -0,0 +1,25 @@
+use crate::storage::{load_visit, save_visit};
+use fyrox::core::{
+    reflect::prelude::*,
+    visitor::{prelude::*, VisitError},
+};
+use std::path::Path;
+
+// ANCHOR: high_score
+// The best score ever reached, it is kept in a file between the runs of the game.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+#[visit(optional)]
+pub struct HighScore {
+    pub score: u32,
+}
+
+impl HighScore {
+    pub fn save(&mut self, path: &Path) -> VisitResult {
+        save_visit(self, "HighScore", path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, VisitError> {
+        load_visit("HighScore", path)
+    }
+}
+// ANCHOR_END: high_score
@@ -0,0 +1,54 @@
+use crate::{
+    player_config::PlayerConfig,
+    storage::{load_visit, save_visit},
+};
+use fyrox::core::{
+    reflect::prelude::*,
+    visitor::{prelude::*, VisitError},
+};
+use std::path::Path;
+
+// ANCHOR: settings
+// Options that can be changed from the settings menu, they are kept in a file between the runs of
+// the game.
+#[derive(Visit, Reflect, Debug, Clone, PartialEq)]
+#[visit(optional)]
+pub struct Settings {
+    // Degrees of camera rotation per one unit of raw mouse movement.
+    pub mouse_sensitivity: f32,
+
+    // Vertical field of view of the camera (in degrees).
+    pub fov: f32,
+
+    // Gain of all the sounds in the scene, from 0 to 1.
+    pub volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let config = PlayerConfig::default();
+        Self {
+            mouse_sensitivity: config.mouse_sensitivity,
+            fov: config.fov,
+            volume: 1.0,
+        }
+    }
+}
+// ANCHOR_END: settings
+
+// ANCHOR: settings_io
+impl Settings {
+    pub fn apply(&self, config: &mut PlayerConfig) {
+        config.mouse_sensitivity = self.mouse_sensitivity;
+        config.fov = self.fov;
+    }
+
+    pub fn save(&mut self, path: &Path) -> VisitResult {
+        save_visit(self, "Settings", path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, VisitError> {
+        load_visit("Settings", path)
+    }
+}
+// ANCHOR_END: settings_io
@@ -0,0 +1,151 @@
+use crate::settings::Settings;
+use fyrox::{
+    core::{color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        message::{MessageDirection, UiMessage},
+        scroll_bar::{ScrollBarBuilder, ScrollBarMessage},
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::{WidgetBuilder, WidgetMessage},
+        BuildContext, HorizontalAlignment, Thickness, UiNode, UserInterface, VerticalAlignment,
+    },
+};
+
+// ANCHOR: settings_menu_struct
+// A panel with sliders for the settings, it is shown while the game is paused.
+pub struct SettingsMenu {
+    root: Handle<UiNode>,
+    mouse_sensitivity: Handle<UiNode>,
+    fov: Handle<UiNode>,
+    volume: Handle<UiNode>,
+    shown: bool,
+}
+// ANCHOR_END: settings_menu_struct
+
+// ANCHOR: settings_slider
+// Creates a caption and a slider below it, the slider is returned to listen for its changes.
+fn make_slider(
+    ctx: &mut BuildContext,
+    caption: &str,
+    value: f32,
+    min: f32,
+    max: f32,
+    step: f32,
+) -> (Handle<UiNode>, Handle<UiNode>) {
+    let text = TextBuilder::new(WidgetBuilder::new().with_margin(Thickness::uniform(2.0)))
+        .with_text(caption)
+        .build(ctx);
+    let slider = ScrollBarBuilder::new(
+        WidgetBuilder::new()
+            .with_height(24.0)
+            .with_margin(Thickness::uniform(2.0)),
+    )
+    .with_min(min)
+    .with_max(max)
+    .with_step(step)
+    .with_value(value)
+    .show_value(true)
+    .build(ctx);
+    (text, slider)
+}
+// ANCHOR_END: settings_slider
+
+impl SettingsMenu {
+    // ANCHOR: settings_menu_new
+    pub fn new(ui: &mut UserInterface, settings: &Settings) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let (sensitivity_text, mouse_sensitivity) = make_slider(
+            ctx,
+            "Mouse Sensitivity",
+            settings.mouse_sensitivity,
+            0.05,
+            2.0,
+            0.05,
+        );
+        let (fov_text, fov) = make_slider(ctx, "Field of View", settings.fov, 50.0, 110.0, 1.0);
+        let (volume_text, volume) = make_slider(ctx, "Volume", settings.volume, 0.0, 1.0, 0.05);
+
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_width(300.0)
+                .with_visibility(false)
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 200)))
+                .with_child(
+                    StackPanelBuilder::new(
+                        WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(10.0))
+                            .with_child(
+                                TextBuilder::new(WidgetBuilder::new())
+                                    .with_text("Paused")
+                                    .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                                    .build(ctx),
+                            )
+                            .with_child(sensitivity_text)
+                            .with_child(mouse_sensitivity)
+                            .with_child(fov_text)
+                            .with_child(fov)
+                            .with_child(volume_text)
+                            .with_child(volume),
+                    )
+                    .build(ctx),
+                ),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            mouse_sensitivity,
+            fov,
+            volume,
+            shown: false,
+        }
+    }
+    // ANCHOR_END: settings_menu_new
+
+    // ANCHOR: settings_menu_visibility
+    pub fn is_visible(&self) -> bool {
+        self.shown
+    }
+
+    pub fn set_visible(&mut self, ui: &mut UserInterface, visible: bool) {
+        if self.shown != visible {
+            self.shown = visible;
+            ui.send_message(WidgetMessage::visibility(
+                self.root,
+                MessageDirection::ToWidget,
+                visible,
+            ));
+        }
+    }
+    // ANCHOR_END: settings_menu_visibility
+
+    // ANCHOR: settings_menu_message
+    // Writes new values of the sliders to the settings, returns true if something has changed.
+    pub fn handle_ui_message(&self, message: &UiMessage, settings: &mut Settings) -> bool {
+        if message.direction() != MessageDirection::FromWidget {
+            return false;
+        }
+        let Some(ScrollBarMessage::Value(value)) = message.data::<ScrollBarMessage>() else {
+            return false;
+        };
+
+        let destination = message.destination();
+        let setting = if destination == self.mouse_sensitivity {
+            &mut settings.mouse_sensitivity
+        } else if destination == self.fov {
+            &mut settings.fov
+        } else if destination == self.volume {
+            &mut settings.volume
+        } else {
+            return false;
+        };
+        *setting = *value;
+        true
+    }
+    // ANCHOR_END: settings_menu_message
+}
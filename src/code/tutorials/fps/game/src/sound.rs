@@ -0,0 +1,34 @@
+use fyrox::{
+    core::{algebra::Vector3, pool::Handle},
+    scene::{
+        base::BaseBuilder,
+        graph::Graph,
+        node::Node,
+        sound::{SoundBufferResource, SoundBuilder, Status},
+        transform::TransformBuilder,
+    },
+};
+
+// ANCHOR: play_sound
+// Creates a sound source at the given position, that plays the buffer once and then removes
+// itself from the scene.
+pub fn play_sound(
+    graph: &mut Graph,
+    buffer: &SoundBufferResource,
+    position: Vector3<f32>,
+    gain: f32,
+) -> Handle<Node> {
+    SoundBuilder::new(
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .build(),
+        ),
+    )
+    .with_buffer(Some(buffer.clone()))
+    .with_gain(gain)
+    .with_status(Status::Playing)
+    .with_play_once(true)
+    .build(graph)
+}
+// ANCHOR_END: play_sound
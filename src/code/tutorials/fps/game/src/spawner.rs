@@ -0,0 +1,113 @@
+use crate::Game;
+use fyrox::{
+    core::{
+        algebra::UnitQuaternion, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        variable::InheritableVariable, visitor::prelude::*,
+    },
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::node::Node,
+    script::{ScriptContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "2af8eabf-9bb3-4977-8959-85292280db87")]
+#[visit(optional)]
+pub struct Spawner {
+    // ANCHOR: spawner_fields
+    // A prefab of an enemy that will be instantiated in every wave.
+    enemy_prefab: InheritableVariable<Option<ModelResource>>,
+
+    // Points at which the enemies appear, they're used in turns. The spawner itself is used
+    // when there are none.
+    spawn_points: InheritableVariable<Vec<Handle<Node>>>,
+
+    // Amount of enemies in the first wave.
+    enemies_per_wave: InheritableVariable<u32>,
+
+    // Every next wave has this many more enemies than the previous one.
+    enemies_per_wave_increase: InheritableVariable<u32>,
+
+    // Time (in seconds) between the death of the last enemy and the next wave.
+    wave_delay: InheritableVariable<f32>,
+
+    // Number of the current wave, it starts from 1.
+    #[reflect(hidden)]
+    wave: u32,
+
+    // Enemies of the current wave that are still alive.
+    #[reflect(hidden)]
+    alive: Vec<Handle<Node>>,
+
+    #[reflect(hidden)]
+    wave_timer: f32,
+    // ANCHOR_END: spawner_fields
+}
+
+impl Default for Spawner {
+    fn default() -> Self {
+        Self {
+            enemy_prefab: Default::default(),
+            spawn_points: Default::default(),
+            enemies_per_wave: 3.into(),
+            enemies_per_wave_increase: 2.into(),
+            wave_delay: 5.0.into(),
+            wave: 0,
+            alive: Default::default(),
+            wave_timer: 0.0,
+        }
+    }
+}
+
+impl Spawner {
+    // ANCHOR: spawn_wave
+    fn spawn_wave(&mut self, ctx: &mut ScriptContext) {
+        let Some(enemy_prefab) = self.enemy_prefab.as_ref() else {
+            return;
+        };
+
+        self.wave += 1;
+        let count = *self.enemies_per_wave + (self.wave - 1) * *self.enemies_per_wave_increase;
+        for i in 0..count as usize {
+            let spawn_point = if self.spawn_points.is_empty() {
+                ctx.handle
+            } else {
+                self.spawn_points[i % self.spawn_points.len()]
+            };
+            let Some(spawn_point) = ctx.scene.graph.try_get(spawn_point) else {
+                continue;
+            };
+            let position = spawn_point.global_position();
+
+            let enemy = enemy_prefab.instantiate_at(ctx.scene, position, UnitQuaternion::default());
+            self.alive.push(enemy);
+        }
+    }
+    // ANCHOR_END: spawn_wave
+}
+
+impl ScriptTrait for Spawner {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        if ctx.plugins.get::<Game>().paused {
+            return;
+        }
+
+        // ANCHOR: spawner_update
+        // Dead enemies remove themselves from the scene, so their handles become invalid.
+        let graph = &ctx.scene.graph;
+        self.alive.retain(|enemy| graph.is_valid_handle(*enemy));
+
+        if self.alive.is_empty() {
+            // The first wave starts right away.
+            if self.wave > 0 && self.wave_timer < *self.wave_delay {
+                self.wave_timer += ctx.dt;
+            } else {
+                self.wave_timer = 0.0;
+                self.spawn_wave(ctx);
+            }
+        }
+
+        // The HUD takes the wave number from the plugin.
+        ctx.plugins.get_mut::<Game>().wave = self.wave;
+        // ANCHOR_END: spawner_update
+    }
+}
@@ -0,0 +1,24 @@
+use fyrox::core::{
+    futures::executor::block_on,
+    visitor::{prelude::*, VisitError},
+};
+use std::path::Path;
+
+// ANCHOR: storage
+// Saves anything that could be visited to a binary file, the name is the name of the root region
+// in the file. The same name must be used to load it back.
+pub fn save_visit<T: Visit>(value: &mut T, name: &str, path: &Path) -> VisitResult {
+    let mut visitor = Visitor::new();
+    value.visit(name, &mut visitor)?;
+    visitor.save_binary(path)
+}
+
+// Loads a value that was saved by `save_visit`. Fields that are missing in the file keep their
+// default values, if the type is visited with `#[visit(optional)]`.
+pub fn load_visit<T: Visit + Default>(name: &str, path: &Path) -> Result<T, VisitError> {
+    let mut visitor = block_on(Visitor::load_binary(path))?;
+    let mut value = T::default();
+    value.visit(name, &mut visitor)?;
+    Ok(value)
+}
+// ANCHOR_END: storage
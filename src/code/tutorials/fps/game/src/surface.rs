@@ -0,0 +1,101 @@
+use fyrox::{
+    core::{pool::Handle, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    resource::model::ModelResource,
+    scene::{graph::Graph, node::Node, sound::SoundBufferResource},
+    script::ScriptTrait,
+};
+
+// ANCHOR: surface_kind
+#[derive(Visit, Reflect, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceKind {
+    #[default]
+    Grass,
+    Metal,
+    Wood,
+}
+// ANCHOR_END: surface_kind
+
+// ANCHOR: surface_type
+// Assign this script to a collider of world geometry (or to its rigid body) to make footsteps and
+// impacts on it sound differently.
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "d12b2e14-4c53-49a6-8792-89d189345dbf")]
+#[visit(optional)]
+pub struct SurfaceType {
+    pub kind: SurfaceKind,
+}
+
+impl ScriptTrait for SurfaceType {}
+// ANCHOR_END: surface_type
+
+// ANCHOR: surface_sounds
+// Sound sets for each kind of surface. An empty set means that the default sounds will be used.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+#[visit(optional)]
+pub struct SurfaceSounds {
+    pub grass: Vec<SoundBufferResource>,
+    pub metal: Vec<SoundBufferResource>,
+    pub wood: Vec<SoundBufferResource>,
+}
+
+impl SurfaceSounds {
+    pub fn sounds_for<'a>(
+        &'a self,
+        kind: Option<SurfaceKind>,
+        default: &'a [SoundBufferResource],
+    ) -> &'a [SoundBufferResource] {
+        let sounds = match kind {
+            Some(SurfaceKind::Grass) => &self.grass,
+            Some(SurfaceKind::Metal) => &self.metal,
+            Some(SurfaceKind::Wood) => &self.wood,
+            None => return default,
+        };
+        if sounds.is_empty() {
+            default
+        } else {
+            sounds
+        }
+    }
+}
+// ANCHOR_END: surface_sounds
+
+// ANCHOR: surface_effects
+// Effect prefabs for each kind of surface, just like the sounds. An unset one means that the
+// default effect will be used.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+#[visit(optional)]
+pub struct SurfaceEffects {
+    pub grass: Option<ModelResource>,
+    pub metal: Option<ModelResource>,
+    pub wood: Option<ModelResource>,
+}
+
+impl SurfaceEffects {
+    pub fn effect_for<'a>(
+        &'a self,
+        kind: Option<SurfaceKind>,
+        default: Option<&'a ModelResource>,
+    ) -> Option<&'a ModelResource> {
+        let effect = match kind {
+            Some(SurfaceKind::Grass) => &self.grass,
+            Some(SurfaceKind::Metal) => &self.metal,
+            Some(SurfaceKind::Wood) => &self.wood,
+            None => return default,
+        };
+        effect.as_ref().or(default)
+    }
+}
+// ANCHOR_END: surface_effects
+
+// ANCHOR: find_surface_kind
+// Things without the surface type script have no specific kind, `None` is returned for them.
+pub fn find_surface_kind(graph: &Graph, collider: Handle<Node>) -> Option<SurfaceKind> {
+    let parent = graph.try_get(collider)?.parent();
+    [collider, parent].into_iter().find_map(|handle| {
+        graph
+            .try_get(handle)
+            .and_then(|node| node.try_get_script::<SurfaceType>())
+            .map(|surface| surface.kind)
+    })
+}
+// ANCHOR_END: find_surface_kind
@@ -0,0 +1,105 @@
+use crate::{
+    sound::play_sound,
+    trigger::{update_trigger, with_player, TriggerHandler, TriggerVolume},
+};
+use fyrox::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{node::Node, rigidbody::RigidBody, sound::SoundBufferResource},
+    script::{ScriptContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "9f8cd0cd-8a37-4653-a3b4-bfb1131ffe2d")]
+#[visit(optional)]
+pub struct Teleporter {
+    // ANCHOR: teleporter_fields
+    // A node where the player appears. Usually it is another teleporter that leads back, but it
+    // could be any node.
+    destination: InheritableVariable<Handle<Node>>,
+
+    // Time (in seconds) during which the destination teleporter ignores the player, otherwise the
+    // player would be sent back right away.
+    cooldown: InheritableVariable<f32>,
+
+    sound: InheritableVariable<Option<SoundBufferResource>>,
+
+    #[reflect(hidden)]
+    cooldown_timer: f32,
+
+    #[reflect(hidden)]
+    trigger: TriggerVolume,
+    // ANCHOR_END: teleporter_fields
+}
+
+impl Default for Teleporter {
+    fn default() -> Self {
+        Self {
+            destination: Default::default(),
+            cooldown: 1.0.into(),
+            sound: Default::default(),
+            cooldown_timer: 0.0,
+            trigger: Default::default(),
+        }
+    }
+}
+
+// ANCHOR: teleport
+// Angle (in degrees) around the Y axis between the look vector of a node and the Z axis.
+fn yaw_of(node: &Node) -> f32 {
+    let look = node.look_vector();
+    look.x.atan2(look.z).to_degrees()
+}
+
+impl TriggerHandler for Teleporter {
+    fn trigger_volume(&mut self) -> &mut TriggerVolume {
+        &mut self.trigger
+    }
+
+    fn on_enter(&mut self, player: Handle<Node>, ctx: &mut ScriptContext) {
+        if self.cooldown_timer > 0.0 {
+            return;
+        }
+
+        let graph = &mut ctx.scene.graph;
+        let Some(destination) = graph.try_get(*self.destination) else {
+            return;
+        };
+        let position = destination.global_position();
+
+        // The player keeps its direction relative to the teleporter, entering it while looking
+        // forward means looking forward out of the destination too.
+        let yaw_delta = yaw_of(destination) - yaw_of(&graph[ctx.handle]);
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw_delta.to_radians());
+
+        if let Some(rigid_body) = graph.try_get_mut_of_type::<RigidBody>(player) {
+            rigid_body.local_transform_mut().set_position(position);
+            let velocity = rotation * rigid_body.lin_vel();
+            rigid_body.set_lin_vel(velocity);
+        }
+        with_player(graph, player, |player| player.on_teleported(yaw_delta));
+
+        // The player appears inside the destination, it must not send the player back.
+        if let Some(destination) = graph[*self.destination].try_get_script_mut::<Teleporter>() {
+            destination.cooldown_timer = *destination.cooldown;
+        }
+
+        if let Some(sound) = self.sound.as_ref() {
+            play_sound(graph, sound, position, 1.0);
+        }
+    }
+}
+// ANCHOR_END: teleport
+
+impl ScriptTrait for Teleporter {
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        self.cooldown_timer -= ctx.dt;
+        update_trigger(self, ctx);
+    }
+}
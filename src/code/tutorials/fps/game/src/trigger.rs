@@ -0,0 +1,101 @@
+use crate::player::Player;
+use fyrox::{
+    core::{pool::Handle, reflect::prelude::*, visitor::prelude::*},
+    scene::{collider::Collider, graph::Graph, node::Node},
+    script::{ScriptContext, ScriptTrait},
+};
+
+// ANCHOR: find_player_in_trigger
+// Returns a handle of a rigid body with the script of the given type, if the body is inside of a
+// trigger. Colliders of the trigger must be sensors, so the body could pass through them, and they
+// must be children of the node with the trigger script.
+pub fn find_script_in_trigger<T: ScriptTrait>(
+    graph: &Graph,
+    trigger: Handle<Node>,
+) -> Option<Handle<Node>> {
+    graph.try_get(trigger)?.children().iter().find_map(|child| {
+        let collider = graph.try_get_of_type::<Collider>(*child)?;
+        collider
+            .intersects(&graph.physics)
+            .filter(|intersection| intersection.has_any_active_contact)
+            .find_map(|intersection| {
+                let other = if intersection.collider1 == *child {
+                    intersection.collider2
+                } else {
+                    intersection.collider1
+                };
+
+                // The collider is a child of its rigid body, which has the script.
+                let body = graph.try_get(other)?.parent();
+                graph
+                    .try_get(body)
+                    .filter(|body| body.has_script::<T>())
+                    .map(|_| body)
+            })
+    })
+}
+
+// Returns a handle of the player's rigid body, if the player is inside of a trigger.
+pub fn find_player_in_trigger(graph: &Graph, trigger: Handle<Node>) -> Option<Handle<Node>> {
+    find_script_in_trigger::<Player>(graph, trigger)
+}
+// ANCHOR_END: find_player_in_trigger
+
+// ANCHOR: with_player
+// Calls the function for the player script, if the node is the player.
+pub fn with_player(graph: &mut Graph, body: Handle<Node>, func: impl FnOnce(&mut Player)) {
+    if let Some(player) = graph
+        .try_get_mut(body)
+        .and_then(|body| body.try_get_script_mut::<Player>())
+    {
+        func(player);
+    }
+}
+// ANCHOR_END: with_player
+
+// ANCHOR: trigger_volume
+// Remembers the player that is inside a trigger, this way it is possible to tell when the player
+// enters or leaves it.
+#[derive(Visit, Reflect, Default, Debug, Clone)]
+pub struct TriggerVolume {
+    // A rigid body of the player that is currently inside the volume.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    player: Handle<Node>,
+}
+
+impl TriggerVolume {
+    pub fn player(&self) -> Handle<Node> {
+        self.player
+    }
+}
+
+// To make a trigger, add a `TriggerVolume` field to a script, implement this trait for the script
+// and call `update_trigger` in its `on_update` method. Both callbacks are optional.
+pub trait TriggerHandler {
+    fn trigger_volume(&mut self) -> &mut TriggerVolume;
+
+    // Called once when the player's body gets inside the volume.
+    fn on_enter(&mut self, _player: Handle<Node>, _ctx: &mut ScriptContext) {}
+
+    // Called once when the player's body leaves the volume.
+    fn on_exit(&mut self, _player: Handle<Node>, _ctx: &mut ScriptContext) {}
+}
+
+pub fn update_trigger<T: TriggerHandler>(handler: &mut T, ctx: &mut ScriptContext) {
+    let inside = find_player_in_trigger(&ctx.scene.graph, ctx.handle).unwrap_or_default();
+    let volume = handler.trigger_volume();
+    let previous = volume.player;
+    if inside == previous {
+        return;
+    }
+    volume.player = inside;
+
+    if previous.is_some() {
+        handler.on_exit(previous, ctx);
+    }
+    if inside.is_some() {
+        handler.on_enter(inside, ctx);
+    }
+}
+// ANCHOR_END: trigger_volume
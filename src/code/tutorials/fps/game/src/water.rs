@@ -0,0 +1,49 @@
+use crate::trigger::{update_trigger, with_player, TriggerHandler, TriggerVolume};
+use fyrox::{
+    core::{pool::Handle, reflect::prelude::*, type_traits::prelude::*, visitor::prelude::*},
+    scene::node::Node,
+    script::{ScriptContext, ScriptDeinitContext, ScriptTrait},
+};
+
+#[derive(Visit, Reflect, Default, Debug, Clone, TypeUuidProvider, ComponentProvider)]
+#[type_uuid(id = "5d54448a-3cb3-4efa-801f-911b78f2a6a7")]
+#[visit(optional)]
+pub struct WaterVolume {
+    // ANCHOR: water_volume_fields
+    #[reflect(hidden)]
+    trigger: TriggerVolume,
+    // ANCHOR_END: water_volume_fields
+}
+
+// ANCHOR: water_volume_update
+// The player tracks how many volumes it is in, so it must be notified only when it enters or
+// leaves this one.
+impl TriggerHandler for WaterVolume {
+    fn trigger_volume(&mut self) -> &mut TriggerVolume {
+        &mut self.trigger
+    }
+
+    fn on_enter(&mut self, player: Handle<Node>, ctx: &mut ScriptContext) {
+        with_player(&mut ctx.scene.graph, player, |player| player.enter_water());
+    }
+
+    fn on_exit(&mut self, player: Handle<Node>, ctx: &mut ScriptContext) {
+        with_player(&mut ctx.scene.graph, player, |player| player.exit_water());
+    }
+}
+// ANCHOR_END: water_volume_update
+
+impl ScriptTrait for WaterVolume {
+    // ANCHOR: water_volume_deinit
+    fn on_deinit(&mut self, ctx: &mut ScriptDeinitContext) {
+        // The player must not keep swimming, if the volume was removed while the player was in it.
+        with_player(&mut ctx.scene.graph, self.trigger.player(), |player| {
+            player.exit_water()
+        });
+    }
+    // ANCHOR_END: water_volume_deinit
+
+    fn on_update(&mut self, ctx: &mut ScriptContext) {
+        update_trigger(self, ctx);
+    }
+}
@@ -1,6 +1,6 @@
 use fyrox::{
     core::{
-        math, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
+        algebra::Vector3, math, pool::Handle, reflect::prelude::*, type_traits::prelude::*,
         variable::InheritableVariable, visitor::prelude::*,
     },
     resource::{model::ModelResource, model::ModelResourceExtension},
@@ -32,6 +32,88 @@ pub struct Weapon {
     // ANCHOR_END: animation_player
 }
 
+// ANCHOR: weapon_slot
+// A weapon that the player carries, along with its stats and the rounds left in its magazine.
+#[derive(Visit, Reflect, Debug, Clone)]
+#[visit(optional)]
+pub struct WeaponSlot {
+    // A node with the `Weapon` script, it receives shoot messages.
+    pub weapon: Handle<Node>,
+
+    // Visual model of the weapon, it is shown only while the weapon is selected.
+    pub model: Handle<Node>,
+
+    // Amount of health that a single shot takes from the thing it hits.
+    pub damage: f32,
+
+    // How many shots per second could be made while the fire button is held.
+    pub fire_rate: f32,
+
+    pub magazine_size: u32,
+
+    // How many targets a single shot could pierce after the first one.
+    pub max_penetrations: u32,
+
+    // Part of the damage that is left after every pierced target.
+    pub penetration_damage: f32,
+
+    // Shots deal full damage up to the start distance, then it goes down linearly until the end
+    // distance, where only the minimal part of the damage is left.
+    pub falloff_start: f32,
+    pub falloff_end: f32,
+    pub min_damage_fraction: f32,
+
+    #[reflect(hidden)]
+    pub ammo: u32,
+
+    // Local position of the model when the player aims down sights, usually it is centered and
+    // raised to the eye. The model stays at rest while aiming, if it is not set.
+    pub ads_position: Option<Vector3<f32>>,
+
+    // Local position of the model when it does not sway or bob.
+    #[visit(skip)]
+    #[reflect(hidden)]
+    pub rest_position: Vector3<f32>,
+}
+// ANCHOR_END: weapon_slot
+
+impl Default for WeaponSlot {
+    fn default() -> Self {
+        Self {
+            weapon: Default::default(),
+            model: Default::default(),
+            damage: 20.0,
+            fire_rate: 10.0,
+            magazine_size: 30,
+            max_penetrations: 0,
+            penetration_damage: 0.5,
+            falloff_start: 20.0,
+            falloff_end: 50.0,
+            min_damage_fraction: 0.5,
+            ammo: 30,
+            ads_position: None,
+            rest_position: Default::default(),
+        }
+    }
+}
+
+// ANCHOR: damage_falloff
+impl WeaponSlot {
+    // Part of the damage that a shot deals to a target at the given distance.
+    pub fn damage_falloff(&self, distance: f32) -> f32 {
+        let length = self.falloff_end - self.falloff_start;
+        let t = if length > 0.0 {
+            ((distance - self.falloff_start) / length).clamp(0.0, 1.0)
+        } else if distance > self.falloff_start {
+            1.0
+        } else {
+            0.0
+        };
+        1.0 + (self.min_damage_fraction - 1.0) * t
+    }
+}
+// ANCHOR_END: damage_falloff
+
 // ANCHOR: shoot_message
 #[derive(Debug)]
 pub struct ShootWeaponMessage {}